@@ -1,163 +1,214 @@
-// pub mod i2c {
-//     use embassy_rp::i2c::SclPin as SclPinTrait;
-//     use embassy_rp::i2c::SdaPin as SdaPinTrait;
-//     use embassy_rp::i2c_slave;
-//     use embassy_rp::peripherals::*;
-//     use static_assertions::assert_impl_all as assert_impl;
-
-//     macro_rules! define_i2c_config {
-//         (
-//             peripheral: $i2c_peripheral:ty,
-//             scl_pin: $scl_pin:ty,
-//             sda_pin: $sda_pin:ty,
-//             addr: $addr:expr,
-//             general_call: $general_call:expr,
-//             scl_pullup: $scl_pullup:expr,
-//             sda_pullup: $sda_pullup:expr,
-//         ) => {
-//             // Asserts that the types of the given SLC pin, SDA, and I2C Peripheral are valid
-//             assert_impl!($scl_pin: SclPinTrait<$i2c_peripheral>);
-//             assert_impl!($sda_pin: SdaPinTrait<$i2c_peripheral>);
-
-//             pub type I2cPeripheral = $i2c_peripheral;
-
-//             /// Gets the correct peripherals based on configured I2C
-//             #[macro_export]
-//             macro_rules! get_i2c_peripherals {
-//                 ($peripherals:ident) => {
-//                     pastey::paste! { ($peripherals.[<$i2c_peripheral>], $peripherals.[<$scl_pin>], $peripherals.[<$sda_pin>]) }
-//                 }
-//             }
-            
-//             /// Binds the i2c interrupt corresponding to the provided `i2c_peripheral`
-//             #[macro_export]
-//             macro_rules! bind_i2c_interrupt {
-//                 () => {
-//                     pastey::paste! {
-//                         bind_interrupts!(struct I2cIrq {
-//                             [<$i2c_peripheral _IRQ>] => i2c::InterruptHandler<$i2c_peripheral>;
-//                         });
-//                     } 
-//                 }
-//             }
-
-//             /// Initilizes a new [`i2c_slave::Config`] object given the config values set in config module
-//             pub fn new() -> i2c_slave::Config {
-//                 let mut config = i2c_slave::Config::default();
-//                 config.addr = $addr;
-//                 config.general_call = $general_call;
-//                 config.scl_pullup = $scl_pullup;
-//                 config.sda_pullup = $sda_pullup;
-
-//                 config
-//             }
-//         };
-//     }
-
-//     define_i2c_config! {
-//         peripheral: I2C0,
-//         scl_pin: PIN_1,
-//         sda_pin: PIN_0,
-//         addr: 0x60,
-//         general_call: false,
-//         scl_pullup: false,
-//         sda_pullup: false,
-//     }
-// }
-
-pub mod spi {
-    use embassy_rp::spi::{
-        self,
-        Phase, Polarity, 
-        ClkPin, MosiPin, MisoPin
-    };
+pub mod i2c {
+    use embassy_rp::i2c::SclPin as SclPinTrait;
+    use embassy_rp::i2c::SdaPin as SdaPinTrait;
+    use embassy_rp::i2c_slave;
     use embassy_rp::peripherals::*;
     use static_assertions::assert_impl_all as assert_impl;
 
-    macro_rules! define_spi_config {
+    macro_rules! define_i2c_config {
         (
-            peripheral: $spi_peri:ty,
-            clock_pin: $clk_pin:ty,
-            mosi_pin: $mosi_pin:ty,
-            miso_pin: $miso_pin:ty,
-            frequency: $frequency:expr,
-            phase: $phase:expr,
-            polarity: $polarity:expr,
-            sync_threshhold: $sync_threshold:expr,
-            // dummy_spi_peripheral: $dummy_spi_peri:ty,
-            // dummy_clock_pin: $dummy_clk_pin:ty,
-            // dummy_mosi_pin: $dummy_mosi_pin:ty,
-            // dummy_miso_pin: $dummy_miso_pin:ty,
-            // rx_dma: $rx_dma:ty,
-            // tx_dma: $tx_dma:ty,
-            // dummy_rx_dma: $dummy_rx_dma:ty,
-            // dummy_tx_dma: $dummy_tx_dma:ty
+            peripheral: $i2c_peripheral:ty,
+            scl_pin: $scl_pin:ty,
+            sda_pin: $sda_pin:ty,
+            addr: $addr:expr,
+            general_call: $general_call:expr,
+            scl_pullup: $scl_pullup:expr,
+            sda_pullup: $sda_pullup:expr,
         ) => {
-            assert_impl!($clk_pin: ClkPin<$spi_peri>);
-            assert_impl!($mosi_pin: MosiPin<$spi_peri>);
-            assert_impl!($miso_pin: MisoPin<$spi_peri>);
+            // Asserts that the types of the given SLC pin, SDA, and I2C Peripheral are valid
+            assert_impl!($scl_pin: SclPinTrait<$i2c_peripheral>);
+            assert_impl!($sda_pin: SdaPinTrait<$i2c_peripheral>);
 
-            // assert_impl!($dummy_clk_pin: ClkPin<$dummy_spi_peri>);
-            // assert_impl!($dummy_mosi_pin: MosiPin<$dummy_spi_peri>);
-            // assert_impl!($dummy_miso_pin: MisoPin<$dummy_spi_peri>);
+            pub type I2cPeripheral = $i2c_peripheral;
 
-            pub type SpiPeripheral = $spi_peri;
-            // pub type DummySpiPeripheral = $dummy_spi_peri;
-
-            /// Gets the correct peripherals based on the values configered in [`define_spi_config!`]
-            // #[cfg(not(feature = "dummy-spi"))]
+            /// Gets the correct peripherals based on configured I2C
             #[macro_export]
-            macro_rules! get_spi_peripherals {
+            macro_rules! get_i2c_peripherals {
                 ($peripherals:ident) => {
-                    ::pastey::paste!{ ($peripherals.[<$spi_peri>], $peripherals.[<$clk_pin>], $peripherals.[<$mosi_pin>], $peripherals.[<$miso_pin>]) }
+                    pastey::paste! { ($peripherals.[<$i2c_peripheral>], $peripherals.[<$scl_pin>], $peripherals.[<$sda_pin>]) }
                 }
             }
 
-            // #[cfg(feature = "dummy-spi")]
-            // #[macro_export]
-            // macro_rules! get_spi_peripherals {
-            //     ($peripherals:ident) => {
-            //         ::pastey::paste!{(
-            //             $peripherals.[<$spi_peri>], $peripherals.[<$clk_pin>], $peripherals.[<$mosi_pin>], $peripherals.[<$miso_pin>], $peripherals.[<$rx_dma>], $peripherals.[<$tx_dma>],
-            //             $peripherals.[<$dummy_spi_peri>], $peripherals.[<$dummy_clk_pin>], $peripherals.[<$dummy_mosi_pin>], $peripherals.[<$dummy_miso_pin>], $peripherals.[<$dummy_rx_dma>], $peripherals.[<$dummy_tx_dma>]
-            //         )}
-            //     }
-            // }
-
-            /// Initlizes a new [`spi::Config`] object given the values configered in [`define_spi_config!`]
-            pub fn new() -> spi::Config {
-                let mut config = spi::Config::default();
-                config.frequency = $frequency;
-                config.phase = $phase;
-                config.polarity = $polarity;
+            /// Binds the i2c interrupt corresponding to the provided `i2c_peripheral`
+            #[macro_export]
+            macro_rules! bind_i2c_interrupt {
+                () => {
+                    pastey::paste! {
+                        bind_interrupts!(struct I2cIrq {
+                            [<$i2c_peripheral _IRQ>] => i2c::InterruptHandler<$i2c_peripheral>;
+                        });
+                    }
+                }
+            }
+
+            /// Initilizes a new [`i2c_slave::Config`] object given the config values set in config module
+            pub fn new() -> i2c_slave::Config {
+                let mut config = i2c_slave::Config::default();
+                config.addr = $addr;
+                config.general_call = $general_call;
+                config.scl_pullup = $scl_pullup;
+                config.sda_pullup = $sda_pullup;
 
                 config
             }
+        };
+    }
+
+    define_i2c_config! {
+        peripheral: I2C0,
+        scl_pin: PIN_1,
+        sda_pin: PIN_0,
+        addr: 0x60,
+        general_call: false,
+        scl_pullup: false,
+        sda_pullup: false,
+    }
+}
+
+pub mod spi {
+    use embassy_rp::spi::{
+        self,
+        Phase, Polarity,
+        ClkPin as ClkPinTrait, MosiPin as MosiPinTrait, MisoPin as MisoPinTrait,
+    };
+    use embassy_rp::dma::Channel as DmaChannel;
+    use embassy_rp::peripherals::*;
+    use embassy_rp::Peri;
+    use static_assertions::assert_impl_all as assert_impl;
+
+    pub type SpiPeripheral = SPI0;
+    pub type RxDmaChannel = DMA_CH2;
+    pub type TxDmaChannel = DMA_CH3;
+
+    /// Initlizes a new [`spi::Config`] object for the command link.
+    pub fn new() -> spi::Config {
+        let mut config = spi::Config::default();
+        config.frequency = FREQUENCY;
+        config.phase = Phase::CaptureOnFirstTransition;
+        config.polarity = Polarity::IdleLow;
+
+        config
+    }
+
+    pub const FREQUENCY: u64 = 12_500_000;
+    pub const SYNC_THRESHOLD: u8 = 3;
+    /// Selects half-duplex (telemetry only sent on request) vs full-duplex (telemetry
+    /// streamed in lockstep with every command frame) operation for [`core0::spi_task`].
+    pub const HALF_DUPLEX: bool = false;
+
+    /// Declares a pin-selection enum whose variants are exactly the pins on this board that
+    /// implement `$trait` for `$peripheral`, plus an `Into` conversion from each pin's concrete
+    /// embassy-hal type. A board variant that rewires a link picks a different variant at the
+    /// call site instead of editing this module.
+    macro_rules! define_spi_pin_enum {
+        ($name:ident: $trait:ident<$peripheral:ty> { $($variant:ident($pin:ty)),+ $(,)? }) => {
+            pub enum $name<'d> {
+                $($variant(Peri<'d, $pin>)),+
+            }
+
+            $(
+                assert_impl!($pin: $trait<$peripheral>);
 
-            pub const FREQUENCY: u64 = $frequency;
-            pub const SYNC_THRESHOLD: u8 = $sync_threshold;
+                impl<'d> From<Peri<'d, $pin>> for $name<'d> {
+                    fn from(pin: Peri<'d, $pin>) -> Self {
+                        $name::$variant(pin)
+                    }
+                }
+            )+
         };
     }
 
-    define_spi_config! {
-        peripheral: SPI0,
-        clock_pin: PIN_2,
-        mosi_pin: PIN_3,
-        miso_pin: PIN_4,
-        frequency: 12_500_000,
-        phase: Phase::CaptureOnFirstTransition,
-        polarity: Polarity::IdleLow,
-        sync_threshhold: 3,
-
-        // // The following are only used when the dummy spi feature is enabled
-        // dummy_spi_peripheral: SPI1,
-        // dummy_clock_pin: PIN_10,
-        // dummy_mosi_pin: PIN_11,
-        // dummy_miso_pin: PIN_28,
-        // rx_dma: DMA_CH2,
-        // tx_dma: DMA_CH3,
-        // dummy_rx_dma: DMA_CH4,
-        // dummy_tx_dma: DMA_CH5
+    // `core0::spi_task` drives the SSP through the PAC directly rather than through
+    // `embassy_rp::spi::Spi` (see the comment above `RX_DMA_CHANNEL` in core0.rs), so these
+    // enums exist to keep the declared wiring's compile-time validity guarantee, the same job
+    // `assert_impl!` did before, without nailing the pin choice to this module.
+    define_spi_pin_enum!(ClkPin: ClkPinTrait<SpiPeripheral> {
+        Pin2(PIN_2),
+        Pin6(PIN_6),
+    });
+    define_spi_pin_enum!(MosiPin: MosiPinTrait<SpiPeripheral> {
+        Pin3(PIN_3),
+        Pin7(PIN_7),
+    });
+    define_spi_pin_enum!(MisoPin: MisoPinTrait<SpiPeripheral> {
+        Pin4(PIN_4),
+        Pin20(PIN_20),
+    });
+
+    /// Builds an async, DMA-backed master-mode SPI bus out of any pin/DMA-channel combination
+    /// valid for `P`. Generic over the peripheral so the same constructor serves both
+    /// [`SpiPeripheral`] (were it ever driven through embassy instead of the PAC) and
+    /// [`LoopbackPeripheral`]'s on-bench self-test below.
+    ///
+    /// There's no equivalent `new_slave` here: `embassy_rp::spi::Spi` has no slave-mode support
+    /// at all (see the comment above `RX_DMA_CHANNEL` in `core0.rs`), so this module can only
+    /// ever build master-mode buses. The board's real SPI slave — the command link a host talks
+    /// to — is `core0::spi_task`, which drives the SSP0 peripheral directly through the PAC and
+    /// its own paired RX/TX DMA channels instead of going through this `Spi` abstraction.
+    pub fn new_master<'d, P, C, M, S, TxCh, RxCh>(
+        peripheral: Peri<'d, P>,
+        clk: Peri<'d, C>,
+        mosi: Peri<'d, M>,
+        miso: Peri<'d, S>,
+        tx_dma: Peri<'d, TxCh>,
+        rx_dma: Peri<'d, RxCh>,
+        config: spi::Config,
+    ) -> spi::Spi<'d, P, spi::Async>
+    where
+        P: spi::Instance,
+        C: ClkPinTrait<P>,
+        M: MosiPinTrait<P>,
+        S: MisoPinTrait<P>,
+        TxCh: DmaChannel,
+        RxCh: DmaChannel,
+    {
+        spi::Spi::new(peripheral, clk, mosi, miso, tx_dma, rx_dma, config)
+    }
+
+    /// GPIO alternate-function select value that routes a pin to the SSP/SPI peripheral
+    /// (RP2040 datasheet table 2.19.2). Shared with `core0.rs`'s MISO hi-Z/SPI toggle and its
+    /// raw SSP0 slave-mode bring-up, since both talk to the SSP through the PAC directly.
+    pub const SPI_FUNCSEL: u8 = 1;
+
+    /// GPIO `core0::spi_task`'s `cs_pin` is wired to. Read as a plain GPIO edge rather than the
+    /// SSP's hardware frame-select, so the task can notice CS falling independent of the SSP's
+    /// own byte framing; unlike [`ClkPin`]/[`MosiPin`]/[`MisoPin`] this has no peripheral trait
+    /// to assert against (any RP2040 GPIO works as an `Input`), so it's a plain constant instead
+    /// of a pin-selection enum.
+    pub const CS_PIN_NUM: usize = 21;
+
+    /// Second, otherwise-unused SPI peripheral kept wired up purely for on-bench verification:
+    /// [`core0::spi_self_test_task`] drives it in master mode over a bench jumper looping its
+    /// own MOSI back to its own MISO, so a flashed board can confirm its DMA/pin setup actually
+    /// moves bytes before anyone connects the real command-link host to [`SpiPeripheral`].
+    #[cfg(feature = "spi-self-test")]
+    pub type LoopbackPeripheral = SPI1;
+    #[cfg(feature = "spi-self-test")]
+    pub type LoopbackTxDmaChannel = DMA_CH4;
+    #[cfg(feature = "spi-self-test")]
+    pub type LoopbackRxDmaChannel = DMA_CH5;
+
+    #[cfg(feature = "spi-self-test")]
+    define_spi_pin_enum!(LoopbackClkPin: ClkPinTrait<LoopbackPeripheral> {
+        Pin10(PIN_10),
+    });
+    #[cfg(feature = "spi-self-test")]
+    define_spi_pin_enum!(LoopbackMosiPin: MosiPinTrait<LoopbackPeripheral> {
+        Pin11(PIN_11),
+    });
+    #[cfg(feature = "spi-self-test")]
+    define_spi_pin_enum!(LoopbackMisoPin: MisoPinTrait<LoopbackPeripheral> {
+        Pin8(PIN_8),
+    });
+
+    #[cfg(feature = "spi-self-test")]
+    pub fn loopback_config() -> spi::Config {
+        let mut config = spi::Config::default();
+        config.frequency = FREQUENCY;
+        config.phase = Phase::CaptureOnFirstTransition;
+        config.polarity = Polarity::IdleLow;
+
+        config
     }
 }
 
@@ -165,138 +216,138 @@ pub mod dshot {
     use static_assertions::assert_impl_all as assert_impl;
     use embassy_rp::peripherals::*;
     use embassy_rp::pio::{self, Pio, PioPin, Pin, Instance, StateMachine};
-    use rp2040_dshot::encoder::DShotSpeed;
+    use rp2040_dshot::encoder::{DShotSpeed, MotorConfig};
     use embassy_rp::Peri;
     use fixed::FixedU32;
     use fixed::types::extra::U8;
+    use core::num::NonZeroU8;
+
+    /// Declares a pin-selection enum whose variants are exactly the pins on this board wired
+    /// to one PIO's four DShot state machines, plus an `Into` conversion from each pin's
+    /// concrete embassy-hal type and a helper that hands the chosen one to [`pio::Common`].
+    /// Rewiring a leg to a different physical pin means picking a different variant at
+    /// [`set_pio_config`]'s call site instead of editing and recompiling this module.
+    macro_rules! define_dshot_pin_enum {
+        ($name:ident { $($variant:ident($pin:ty)),+ $(,)? }) => {
+            pub enum $name<'d> {
+                $($variant(Peri<'d, $pin>)),+
+            }
 
-    
+            $(
+                assert_impl!($pin: PioPin);
 
-    macro_rules! define_dshot_config {
-        (
-            top_front_right_pin: $top_front_right_pin:ty,
-            top_front_left_pin: $top_front_left_pin:ty,
-            top_back_right_pin: $top_back_right_pin:ty,
-            top_back_left_pin: $top_back_left_pin:ty,
-            bottom_front_right_pin: $bottom_front_right_pin:ty,
-            bottom_front_left_pin: $bottom_front_left_pin:ty,
-            bottom_back_right_pin: $bottom_back_right_pin:ty,
-            bottom_back_left_pin: $bottom_back_left_pin:ty,
-            dshot_speed: $dshot_speed:expr,
-            pio_clock_hz: $pio_clock:expr,
-            update_rate_hz: $update_rate:expr
-        ) => {
-            // Ensure that all provided pins are valid.
-            assert_impl!($top_front_right_pin: PioPin);
-            assert_impl!($top_front_left_pin: PioPin);
-            assert_impl!($top_back_right_pin: PioPin);
-            assert_impl!($top_back_left_pin: PioPin);
-            assert_impl!($bottom_front_right_pin: PioPin);
-            assert_impl!($bottom_front_left_pin: PioPin);
-            assert_impl!($bottom_back_right_pin: PioPin);
-            assert_impl!($bottom_back_left_pin: PioPin);
-
-
-            /// Gets the correct dshot pins as defined by [`define_dshot_config!`]
-            #[macro_export]
-            macro_rules! get_dshot_pins {
-                ($peripherals:ident) => {
-                    pastey::paste! {(
-                        $peripherals.[<$top_front_right_pin>],
-                        $peripherals.[<$top_front_left_pin>],
-                        $peripherals.[<$top_back_right_pin>],
-                        $peripherals.[<$top_back_left_pin>],
-                        $peripherals.[<$bottom_front_right_pin>],
-                        $peripherals.[<$bottom_front_left_pin>],
-                        $peripherals.[<$bottom_back_right_pin>],
-                        $peripherals.[<$bottom_back_left_pin>],
-                    )}
+                impl<'d> From<Peri<'d, $pin>> for $name<'d> {
+                    fn from(pin: Peri<'d, $pin>) -> Self {
+                        $name::$variant(pin)
+                    }
+                }
+            )+
+
+            impl<'d> $name<'d> {
+                fn into_pio_pin<PIO: Instance>(self, common: &mut pio::Common<'d, PIO>) -> Pin<'d, PIO> {
+                    match self {
+                        $($name::$variant(pin) => common.make_pio_pin(pin)),+
+                    }
                 }
             }
-            
-            #[allow(clippy::too_many_arguments)]
-            pub fn set_pio_config<'d>
-            (
-                pio0: &mut Pio<'d, PIO0>, 
-                pio1: &mut Pio<'d, PIO1>,
-                top_front_right_pin: Peri<'d, $top_front_right_pin>,
-                top_front_left_pin: Peri<'d, $top_front_left_pin>,
-                top_back_right_pin: Peri<'d, $top_back_right_pin>,
-                top_back_left_pin: Peri<'d, $top_back_left_pin>,
-                bottom_front_right_pin: Peri<'d, $bottom_front_right_pin>,
-                bottom_front_left_pin: Peri<'d, $bottom_front_left_pin>,
-                bottom_back_right_pin: Peri<'d, $bottom_back_right_pin>,
-                bottom_back_left_pin: Peri<'d, $bottom_back_left_pin>,
-            ) {
-                let top_front_right_pin = pio0.common.make_pio_pin(top_front_right_pin);
-                let top_front_left_pin = pio0.common.make_pio_pin(top_front_left_pin);
-                let top_back_right_pin = pio0.common.make_pio_pin(top_back_right_pin);
-                let top_back_left_pin = pio0.common.make_pio_pin(top_back_left_pin);
-                let bottom_front_right_pin = pio1.common.make_pio_pin(bottom_front_right_pin);
-                let bottom_front_left_pin = pio1.common.make_pio_pin(bottom_front_left_pin);
-                let bottom_back_right_pin = pio1.common.make_pio_pin(bottom_back_right_pin);
-                let bottom_back_left_pin = pio1.common.make_pio_pin(bottom_back_left_pin);
-
-                set_sm_config(&mut pio0.sm0, &top_front_right_pin);
-                set_sm_config(&mut pio0.sm1, &top_front_left_pin);
-                set_sm_config(&mut pio0.sm2, &top_back_right_pin);
-                set_sm_config(&mut pio0.sm3, &top_back_left_pin);
-                set_sm_config(&mut pio1.sm0, &bottom_front_right_pin);
-                set_sm_config(&mut pio1.sm1, &bottom_front_left_pin);
-                set_sm_config(&mut pio1.sm2, &bottom_back_right_pin);
-                set_sm_config(&mut pio1.sm3, &bottom_back_left_pin);
-            } 
-
-            fn set_sm_config<'d, PIO: Instance, const SM: usize> (
-                sm: &mut StateMachine<'d, PIO, SM>,
-                pin: &Pin<'d, PIO>
-            ) {
-                let mut config = pio::Config::<PIO>::default();
-                config.clock_divider = PIO_CLOCK_DIVIDER;
-
-                config.set_set_pins(&[pin]);
-                config.set_out_pins(&[pin]);
-
-                sm.set_config(&config);
-            }
+        };
+    }
 
-            pub const DSHOT_SPEED: DShotSpeed = $dshot_speed;
-            pub const PIO_CLOCK_HZ: u32 = $pio_clock;
-            pub const UPDATE_RATE_HZ: u32 = $update_rate;
+    define_dshot_pin_enum!(Pio0DshotPin {
+        Pin13(PIN_13),
+        Pin14(PIN_14),
+        Pin15(PIN_15),
+        Pin16(PIN_16),
+    });
+
+    define_dshot_pin_enum!(Pio1DshotPin {
+        Pin17(PIN_17),
+        Pin18(PIN_18),
+        Pin19(PIN_19),
+        Pin20(PIN_20),
+    });
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_pio_config<'d>
+    (
+        pio0: &mut Pio<'d, PIO0>,
+        pio1: &mut Pio<'d, PIO1>,
+        top_front_right_pin: impl Into<Pio0DshotPin<'d>>,
+        top_front_left_pin: impl Into<Pio0DshotPin<'d>>,
+        top_back_right_pin: impl Into<Pio0DshotPin<'d>>,
+        top_back_left_pin: impl Into<Pio0DshotPin<'d>>,
+        bottom_front_right_pin: impl Into<Pio1DshotPin<'d>>,
+        bottom_front_left_pin: impl Into<Pio1DshotPin<'d>>,
+        bottom_back_right_pin: impl Into<Pio1DshotPin<'d>>,
+        bottom_back_left_pin: impl Into<Pio1DshotPin<'d>>,
+    ) {
+        let top_front_right_pin = top_front_right_pin.into().into_pio_pin(&mut pio0.common);
+        let top_front_left_pin = top_front_left_pin.into().into_pio_pin(&mut pio0.common);
+        let top_back_right_pin = top_back_right_pin.into().into_pio_pin(&mut pio0.common);
+        let top_back_left_pin = top_back_left_pin.into().into_pio_pin(&mut pio0.common);
+        let bottom_front_right_pin = bottom_front_right_pin.into().into_pio_pin(&mut pio1.common);
+        let bottom_front_left_pin = bottom_front_left_pin.into().into_pio_pin(&mut pio1.common);
+        let bottom_back_right_pin = bottom_back_right_pin.into().into_pio_pin(&mut pio1.common);
+        let bottom_back_left_pin = bottom_back_left_pin.into().into_pio_pin(&mut pio1.common);
+
+        set_sm_config(&mut pio0.sm0, &top_front_right_pin);
+        set_sm_config(&mut pio0.sm1, &top_front_left_pin);
+        set_sm_config(&mut pio0.sm2, &top_back_right_pin);
+        set_sm_config(&mut pio0.sm3, &top_back_left_pin);
+        set_sm_config(&mut pio1.sm0, &bottom_front_right_pin);
+        set_sm_config(&mut pio1.sm1, &bottom_front_left_pin);
+        set_sm_config(&mut pio1.sm2, &bottom_back_right_pin);
+        set_sm_config(&mut pio1.sm3, &bottom_back_left_pin);
+    }
 
-            pub const PIO_CLOCK_DIVIDER: FixedU32<U8> = FixedU32::unwrapped_div(
-                FixedU32::<U8>::const_from_int(PIO_CLOCK_HZ),
-                FixedU32::<U8>::const_from_int(DSHOT_SPEED.bit_rate_hz())
-            );
-        };
-    }     
-
-    define_dshot_config! {
-        top_front_right_pin: PIN_13,
-        top_front_left_pin: PIN_14,
-        top_back_right_pin: PIN_15,
-        top_back_left_pin: PIN_16,
-        bottom_front_right_pin: PIN_17,
-        bottom_front_left_pin: PIN_18,
-        bottom_back_right_pin: PIN_19,
-        bottom_back_left_pin: PIN_20,
-        dshot_speed: DShotSpeed::DShot300,
-        pio_clock_hz: 8_000_000,
-        update_rate_hz: 8_000
+    fn set_sm_config<'d, PIO: Instance, const SM: usize> (
+        sm: &mut StateMachine<'d, PIO, SM>,
+        pin: &Pin<'d, PIO>
+    ) {
+        let mut config = pio::Config::<PIO>::default();
+        config.clock_divider = PIO_CLOCK_DIVIDER;
+
+        config.set_set_pins(&[pin]);
+        config.set_out_pins(&[pin]);
+
+        sm.set_config(&config);
     }
+
+    pub const DSHOT_SPEED: DShotSpeed = DShotSpeed::DShot300;
+    pub const PIO_CLOCK_HZ: u32 = 8_000_000;
+    pub const UPDATE_RATE_HZ: u32 = 8_000;
+
+    pub const PIO_CLOCK_DIVIDER: FixedU32<U8> = FixedU32::unwrapped_div(
+        FixedU32::<U8>::const_from_int(PIO_CLOCK_HZ),
+        FixedU32::<U8>::const_from_int(DSHOT_SPEED.bit_rate_hz())
+    );
+
+    /// Motor pole-pair count used to convert decoded eRPM telemetry into mechanical RPM via
+    /// [`MOTOR_CONFIG`]. Same value for all eight motors for now; a per-motor value belongs in
+    /// `flash_config::Config` once the config store needs to support mixed motor types.
+    pub const MOTOR_POLE_PAIRS: NonZeroU8 = match NonZeroU8::new(7) {
+        Some(value) => value,
+        None => panic!("MOTOR_POLE_PAIRS must be nonzero"),
+    };
+
+    /// [`MotorConfig`] for converting eRPM telemetry (`StandardERpmFrame`, `ExtendedERpmFrame`,
+    /// `TelemetryFrame`) to mechanical RPM. `core1::bd_dshot_telemetry_task` applies it; the KISS
+    /// path (`core1::dshot_telemetry_task`) forwards raw frames, and the I2C `ERpm` register
+    /// serves the unconverted eRPM / 100 value.
+    pub const MOTOR_CONFIG: MotorConfig = MotorConfig { pole_pairs: MOTOR_POLE_PAIRS };
 }
 
 pub mod telemetry {
     use static_assertions::assert_impl_all as assert_impl;
     use embassy_rp::peripherals::*;
-    use embassy_rp::uart;
-
+    use embassy_rp::uart::{self, RxPin as RxPinTrait, TxPin as TxPinTrait};
+    use embassy_rp::Peri;
 
     pub fn get_uart_config() -> uart::Config {
         let mut config = uart::Config::default();
 
         // As per KISS ESC specfiication
-        config.baudrate = 115_200; 
+        config.baudrate = 115_200;
         config.data_bits = uart::DataBits::DataBits8;
         config.stop_bits = uart::StopBits::STOP1;
         config.parity = uart::Parity::ParityNone;
@@ -304,60 +355,53 @@ pub mod telemetry {
         config
     }
 
-    macro_rules! define_telemetry_config {
-        (
-            rx_peripheral: $uart_rx:ty,
-            rx_telemetry_pin: $rx_pin:ty,
-            rx_dma_channel: $dma_channel_rx: ty,
-            tx_peripheral: $uart_tx:ty,
-            tx_telemetry_pin: $tx_pin:ty,
-            tx_dma_channel: $dma_channel_tx:ty
-        ) => {
-            // Assert that given telemetry pin(s) is valid
-            assert_impl!($rx_pin: uart::RxPin<$uart_rx>);
-            assert_impl!($tx_pin: uart::TxPin<$uart_tx>);
-
-            #[cfg(not(feature = "dummy-telemetry"))]
-            #[macro_export]
-            macro_rules! get_telemetry_peripherals {
-                ($peripherals:ident) => {
-                    ::pastey::paste!{ ($peripherals.[<$uart_rx>], $peripherals.[<$rx_pin>], $peripherals.[<$dma_channel_rx>]) }
-                }
+    pub type RxPeripheral = UART1;
+    pub type RxDmaChannel = DMA_CH0;
+
+    // The following two are only used when the dummy-telemetry feature is enabled.
+    pub type TxPeripheral = UART0;
+    pub type TxDmaChannel = DMA_CH1;
+
+    /// Declares a pin-selection enum whose variants are exactly the pins on this board that
+    /// implement `$trait` for the given UART peripheral, plus an `Into` conversion from each
+    /// pin's concrete embassy-hal type. A board variant that moves the telemetry wire to a
+    /// different pin picks a different variant at the call site instead of editing this module.
+    macro_rules! define_telemetry_pin_enum {
+        ($name:ident: $trait:ident<$peripheral:ty> { $($variant:ident($pin:ty)),+ $(,)? }) => {
+            pub enum $name<'d> {
+                $($variant(Peri<'d, $pin>)),+
             }
 
-            #[cfg(feature = "dummy-telemetry")]
-            #[macro_export]
-            macro_rules! get_telemetry_peripherals {
-                ($peripherals:ident) => {
-                    ::pastey::paste!{(
-                        $peripherals.[<$uart_rx>], $peripherals.[<$rx_pin>], $peripherals.[<$dma_channel_rx>],
-                        $peripherals.[<$uart_tx>], $peripherals.[<$tx_pin>], $peripherals.[<$dma_channel_tx>],
-                    )}
-                }
-            }
+            $(
+                assert_impl!($pin: $trait<$peripheral>);
 
-            /// Binds the UART interrupt corresponding to the provided `uart_rx`peripheral.
-            #[macro_export]
-            macro_rules! bind_telemetry_interrupt {
-                () => {
-                    ::pastey::paste! { 
-                        ::embassy_rp::bind_interrupts!(struct UartIrq {
-                            [<$uart_rx _IRQ>] => ::embassy_rp::uart::InterruptHandler<::embassy_rp::peripherals::$uart_rx>;
-                        });
+                impl<'d> From<Peri<'d, $pin>> for $name<'d> {
+                    fn from(pin: Peri<'d, $pin>) -> Self {
+                        $name::$variant(pin)
                     }
                 }
-            }
+            )+
         };
     }
 
-    define_telemetry_config! {
-        rx_peripheral: UART1,
-        rx_telemetry_pin: PIN_5,
-        rx_dma_channel: DMA_CH0,
-
-        // The following three are only used when dummy telemetry feature is enabled
-        tx_peripheral: UART0,
-        tx_telemetry_pin: PIN_12,
-        tx_dma_channel: DMA_CH1
+    define_telemetry_pin_enum!(RxPin: RxPinTrait<RxPeripheral> {
+        Pin5(PIN_5),
+        Pin9(PIN_9),
+    });
+
+    // Only used when the dummy-telemetry feature is enabled.
+    define_telemetry_pin_enum!(TxPin: TxPinTrait<TxPeripheral> {
+        Pin12(PIN_12),
+        Pin28(PIN_28),
+    });
+
+    /// Binds the UART interrupt corresponding to [`RxPeripheral`].
+    #[macro_export]
+    macro_rules! bind_telemetry_interrupt {
+        () => {
+            ::embassy_rp::bind_interrupts!(struct UartIrq {
+                UART1_IRQ => ::embassy_rp::uart::InterruptHandler<::embassy_rp::peripherals::UART1>;
+            });
+        }
     }
 }
\ No newline at end of file
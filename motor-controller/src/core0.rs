@@ -1,12 +1,170 @@
 use embassy_rp::gpio::Input;
-use embassy_rp::peripherals::{PIO0, PIO1};
+use embassy_rp::i2c_slave::{Command as I2cCommand, I2cSlave};
+use embassy_rp::peripherals::{I2C0, PIO0, PIO1};
 use rp2040_dshot::encoder::Command as DShotCommand;
-use rp2040_dshot::encoder::{StandardDShotVariant, DShotVariant};
+use rp2040_dshot::encoder::{StandardDShotVariant, DShotVariant, TelemetryFrame};
 use rp2040_dshot::driver::{StandardDShotDriver, DShotDriver};
 use defmt::{error, info, warn};
 use embassy_rp::pac as pac;
+#[cfg(not(feature = "spi-polling"))]
+use embassy_futures::yield_now;
+use embassy_futures::select::{Either, select};
+use embassy_time::{Duration, Ticker};
+
+use core::sync::atomic::Ordering;
+
+use crate::config::dshot::UPDATE_RATE_HZ;
+use crate::flash_config;
+use crate::mixer::{self, CommandVector};
+use crate::{TELEMETRY_BUFFERS, TELEMETRY_LINK_STATS};
+
+/// Register addresses served over the I2C telemetry register map.
+///
+/// A write of a single byte selects the register; the following repeated-start read returns
+/// that register's bytes (little-endian for multi-byte fields). Registers below
+/// [`TelemetryRegister::Overruns`] decode the latest [`TelemetryFrame`]; the rest read the
+/// link-health counters in [`crate::TELEMETRY_LINK_STATS`] directly, so a host can tell a
+/// degrading telemetry link apart from a healthy one reporting boring numbers.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum TelemetryRegister {
+    Temperature = 0x00,
+    Voltage = 0x01,
+    Current = 0x02,
+    Consumption = 0x03,
+    ERpm = 0x04,
+    Overruns = 0x05,
+    FramingErrors = 0x06,
+    ParityErrors = 0x07,
+    Breaks = 0x08,
+    CrcFailures = 0x09,
+    ShortReads = 0x0A,
+    SuccessfulFrames = 0x0B,
+    FramesSinceLastGood = 0x0C,
+}
+
+/// First of eight consecutive register addresses (one per state machine) that accept a 2-byte
+/// little-endian throttle write (see [`i2c_task`]).
+const MOTOR_THROTTLE_BASE_ADDR: u8 = 0x10;
+
+/// A 2-byte write here arms (non-zero) or disarms (zero) the throttle outputs.
+const ARM_REGISTER_ADDR: u8 = 0x18;
+
+/// A 2-byte write here persists or clears the running [`flash_config::Config`]: `1` saves it to
+/// the A/B flash store (see [`flash_config::write_config`]), `2` erases both slots so the next
+/// boot falls back to [`flash_config::Config::DEFAULT`] (see [`flash_config::erase_config`]).
+/// Any other value is ignored.
+const CONFIG_REGISTER_ADDR: u8 = 0x19;
+const CONFIG_OP_SAVE: u8 = 1;
+const CONFIG_OP_ERASE: u8 = 2;
+
+/// A 2-byte write here switches the armed tick between driving the raw per-motor throttles
+/// written through [`MOTOR_THROTTLE_BASE_ADDR`] (non-zero disables it) and driving
+/// [`crate::mixer`]'s 6-DOF mix of the axes written through [`AXIS_BASE_ADDR`] (non-zero enables
+/// it). Switching it on sends `Command::ThreeDModeOn` the same way
+/// [`apply_spin_direction_config`] sends spin-direction commands (six times, to every active
+/// state machine), since the mixer's signed throttles only make sense to an ESC already in
+/// bidirectional (3D) mode.
+const MIXER_ENABLE_REGISTER_ADDR: u8 = 0x1A;
+
+/// First of six consecutive register addresses (one per [`CommandVector`] axis, same order as
+/// [`CommandVector::set_axis`]) that accept a 2-byte little-endian signed write scaling
+/// `i16::MIN..=i16::MAX` onto `-1.0..=1.0` (see [`i2c_task`]).
+const AXIS_BASE_ADDR: u8 = 0x20;
+
+/// Maps an axis-write register address to its [`CommandVector::set_axis`] index, if it's one.
+fn axis_index_from_addr(addr: u8) -> Option<usize> {
+    let idx = addr.checked_sub(AXIS_BASE_ADDR)? as usize;
+    (idx < mixer::AXIS_COUNT).then_some(idx)
+}
 
-use crate::TELEMETRY_BUFFERS;
+/// Maps a throttle-write register address to its state-machine index (0-7), if it's one.
+fn motor_index_from_addr(addr: u8) -> Option<usize> {
+    let idx = addr.checked_sub(MOTOR_THROTTLE_BASE_ADDR)? as usize;
+    (idx < 8).then_some(idx)
+}
+
+impl TelemetryRegister {
+    fn from_addr(addr: u8) -> Option<Self> {
+        match addr {
+            0x00 => Some(Self::Temperature),
+            0x01 => Some(Self::Voltage),
+            0x02 => Some(Self::Current),
+            0x03 => Some(Self::Consumption),
+            0x04 => Some(Self::ERpm),
+            0x05 => Some(Self::Overruns),
+            0x06 => Some(Self::FramingErrors),
+            0x07 => Some(Self::ParityErrors),
+            0x08 => Some(Self::Breaks),
+            0x09 => Some(Self::CrcFailures),
+            0x0A => Some(Self::ShortReads),
+            0x0B => Some(Self::SuccessfulFrames),
+            0x0C => Some(Self::FramesSinceLastGood),
+            _ => None,
+        }
+    }
+
+    /// Serializes the requested field into `out`, returning the number of bytes written.
+    ///
+    /// `frame` is `None` when the latest telemetry buffer failed CRC decoding; link-health
+    /// registers are still served in that case since they're what explain the failure.
+    fn encode(self, frame: Option<&TelemetryFrame>, out: &mut [u8; 4]) -> usize {
+        match self {
+            Self::Temperature => {
+                out[0] = frame.map_or(0, TelemetryFrame::temp);
+                1
+            }
+            Self::Voltage => {
+                out[..2].copy_from_slice(&frame.map_or(0, TelemetryFrame::voltage).to_le_bytes());
+                2
+            }
+            Self::Current => {
+                out[..2].copy_from_slice(&frame.map_or(0, TelemetryFrame::current).to_le_bytes());
+                2
+            }
+            Self::Consumption => {
+                out[..2].copy_from_slice(&frame.map_or(0, TelemetryFrame::consumption).to_le_bytes());
+                2
+            }
+            Self::ERpm => {
+                out[..2].copy_from_slice(&frame.map_or(0, TelemetryFrame::e_rpm).to_le_bytes());
+                2
+            }
+            Self::Overruns => {
+                out.copy_from_slice(&TELEMETRY_LINK_STATS.overruns.load(Ordering::Relaxed).to_le_bytes());
+                4
+            }
+            Self::FramingErrors => {
+                out.copy_from_slice(&TELEMETRY_LINK_STATS.framing_errors.load(Ordering::Relaxed).to_le_bytes());
+                4
+            }
+            Self::ParityErrors => {
+                out.copy_from_slice(&TELEMETRY_LINK_STATS.parity_errors.load(Ordering::Relaxed).to_le_bytes());
+                4
+            }
+            Self::Breaks => {
+                out.copy_from_slice(&TELEMETRY_LINK_STATS.breaks.load(Ordering::Relaxed).to_le_bytes());
+                4
+            }
+            Self::CrcFailures => {
+                out.copy_from_slice(&TELEMETRY_LINK_STATS.crc_failures.load(Ordering::Relaxed).to_le_bytes());
+                4
+            }
+            Self::ShortReads => {
+                out.copy_from_slice(&TELEMETRY_LINK_STATS.short_reads.load(Ordering::Relaxed).to_le_bytes());
+                4
+            }
+            Self::SuccessfulFrames => {
+                out.copy_from_slice(&TELEMETRY_LINK_STATS.successful_frames.load(Ordering::Relaxed).to_le_bytes());
+                4
+            }
+            Self::FramesSinceLastGood => {
+                out.copy_from_slice(&TELEMETRY_LINK_STATS.frames_since_last_good.load(Ordering::Relaxed).to_le_bytes());
+                4
+            }
+        }
+    }
+}
 
 
 pub struct SmDriverBatch {
@@ -50,104 +208,437 @@ macro_rules! for_each_driver {
     }};
 }
 
+/// Same as [`for_each_driver!`], but also binds `$idx` (0-7) so the body can consult
+/// per-motor configuration (active state machine count, spin-direction reversal bitmask).
+macro_rules! for_each_driver_indexed {
+    ($batch: expr, |$driver: ident, $idx: ident| $body:expr) => {{
+        let $idx: usize = 0; let $driver = &mut $batch.pio0_sm0; $body;
+        let $idx: usize = 1; let $driver = &mut $batch.pio0_sm1; $body;
+        let $idx: usize = 2; let $driver = &mut $batch.pio0_sm2; $body;
+        let $idx: usize = 3; let $driver = &mut $batch.pio0_sm3; $body;
+        let $idx: usize = 4; let $driver = &mut $batch.pio1_sm0; $body;
+        let $idx: usize = 5; let $driver = &mut $batch.pio1_sm1; $body;
+        let $idx: usize = 6; let $driver = &mut $batch.pio1_sm2; $body;
+        let $idx: usize = 7; let $driver = &mut $batch.pio1_sm3; $body;
+    }};
+}
 
-async fn write_dshot(sms: &mut SmDriverBatch, buffer: [u8; 2]) {
+/// Sends each motor its configured spin-direction command once at startup, per
+/// `config.spin_direction_reversed`'s per-motor bitmask (bit N = state machine N).
+///
+/// DShot ESCs need 6 repeated transmissions of a spin-direction command before it's accepted,
+/// so this is meant to run once during arming, not per-frame like [`write_dshot`].
+pub async fn apply_spin_direction_config(sms: &mut SmDriverBatch, config: &flash_config::Config) {
+    for_each_driver_indexed!(sms, |driver, idx| {
+        if idx < config.active_sm_count as usize {
+            let reversed = config.spin_direction_reversed & (1 << idx) != 0;
+            let command = if reversed { DShotCommand::SpinDirectonReversed } else { DShotCommand::SpinDirectionNormal };
+
+            for _ in 0..6 {
+                driver.write_command(command, true).await.unwrap_or_else(|err| {
+                    error!("Error while writing spin-direction command to PIOs. Error: {}", err);
+                });
+            }
+        }
+    });
+}
+
+/// Writes a [`crate::mixer::mix_to_throttles`] result out to the per-state-machine DShot path,
+/// one motor per active state machine (see `config.active_sm_count`).
+///
+/// Expects the ESCs to already be in 3D mode (`Command::ThreeDModeOn`, sent the same way
+/// [`apply_spin_direction_config`] sends spin-direction commands), since `mix_to_throttles`
+/// throttles are in the bidirectional range.
+pub async fn write_mixed_throttles(sms: &mut SmDriverBatch, throttles: [u16; 8], config: &flash_config::Config) {
+    for_each_driver_indexed!(sms, |driver, idx| {
+        if idx < config.active_sm_count as usize {
+            driver.write_throttle(throttles[idx], true).await.unwrap_or_else(|err| {
+                error!("Error while writing mixer-commanded throttle to PIOs. Error: {}", err);
+            });
+        }
+    });
+}
+
+async fn write_dshot(sms: &mut SmDriverBatch, buffer: [u8; 2], config: &flash_config::Config) {
     let first_byte = buffer[0];
 
+    if first_byte == crate::firmware_update::DFU_ENTER_OPCODE {
+        // Deferred to spi_task, which owns the DMA exchange state this frame just finished and
+        // the flash handle a DFU session needs.
+        crate::firmware_update::DFU_REQUESTED.store(true, Ordering::Relaxed);
+        return;
+    }
+
     if let Ok(command) = DShotCommand::try_from(first_byte) {
         // Handle as command
-        for_each_driver!(sms, |driver| {
-            driver.write_command(command, true).await.unwrap_or_else(|err| {
-                error!("Error while writing DShot command to PIOs. Error: {}", err);
-            });
+        for_each_driver_indexed!(sms, |driver, idx| {
+            if idx < config.active_sm_count as usize {
+                driver.write_command(command, true).await.unwrap_or_else(|err| {
+                    error!("Error while writing DShot command to PIOs. Error: {}", err);
+                });
+            }
         });
     } else {
         // Handle as throttle
         let raw = u16::from_le_bytes([buffer[0], buffer[1]]);
+        let throttle = raw.clamp(config.throttle_calibration_min, config.throttle_calibration_max);
 
-        let Some(throttle) = raw.checked_sub(raw - 48) else {
-            error!("Invalid raw value: {}", raw);
-            return;
-        };
-
-        for_each_driver!(sms, |driver| {
-            driver.write_throttle(throttle, true).await.unwrap_or_else(|err| {
-                error!("Error while writing Dshot throttle to PIOs. Error {}", err);
-            });
+        for_each_driver_indexed!(sms, |driver, idx| {
+            if idx < config.active_sm_count as usize {
+                driver.write_throttle(throttle, true).await.unwrap_or_else(|err| {
+                    error!("Error while writing Dshot throttle to PIOs. Error {}", err);
+                });
+            }
         });
     }
 }
 
+/// Reserved command-link opcode asking the slave to drive its next transfer with the latest
+/// telemetry word instead of treating it as a DShot command/throttle. `DShotCommand` only
+/// occupies 0-47, so this can never collide with a real ESC command.
+const TELEMETRY_REQUEST_OPCODE: u8 = 0xFF;
+
+fn crc_ok(frame: [u8; 2]) -> bool {
+    StandardDShotVariant::compute_crc(u16::from_le_bytes(frame)) == (frame[1] & 0x0F)
+}
+
 #[embassy_executor::task]
 pub async fn spi_task(
     mut cs_pin: Input<'static>,
-    mut sms: SmDriverBatch
+    mut sms: SmDriverBatch,
+    config: &'static flash_config::Config,
+    flash: &'static crate::firmware_update::SharedFlash,
 ) {
     info!("Spawned core0 executor and spi task!");
-    
+
+    init_ssp0_slave();
+
+    let half_duplex = crate::config::spi::HALF_DUPLEX;
+
     let mut transfer_buffer = [0u8; 2];
 
     // Initialize telemetry buffers
     let mut telemetry_byte_idx = 0;
-    let mut telemetry_buffer = [0u8; 10];
+    let mut telemetry_buffer = [0u8; crate::TELEMETRY_BUFFER_LEN];
     TELEMETRY_BUFFERS.read(&mut telemetry_buffer);
 
     let mut synced_count = 0;
-    
+
     // Sync before sending telemetry to ensure simultanous exchange
     loop {
         cs_pin.wait_for_falling_edge().await;
 
-        read(&mut transfer_buffer);
-
-        let computed_crc = StandardDShotVariant::compute_crc(u16::from_le_bytes(transfer_buffer));
-        let received_crc = transfer_buffer[1] & 0x0F;       
+        read(&mut transfer_buffer).await;
 
-        if computed_crc == received_crc {
-            if synced_count >= crate::spi::SYNC_THRESHOLD { 
+        if crc_ok(transfer_buffer) {
+            if synced_count >= crate::config::spi::SYNC_THRESHOLD {
                 info!("Successfully synced spi!");
-                break; 
+                break;
             }
 
             synced_count += 1;
         } else {
-            wait_one_transmission();
+            wait_one_transmission().await;
             synced_count = 0;
         }
     }
 
+    if half_duplex {
+        set_miso_hi_z();
+    }
+
     loop {
-        // Have we written all 10 bytes?
-        if telemetry_byte_idx == 10 {
+        // Have we written the whole buffer?
+        if telemetry_byte_idx == crate::TELEMETRY_BUFFER_LEN {
             TELEMETRY_BUFFERS.read(&mut telemetry_buffer);
-            // info!("Read {} from telemetry buffers", telemetry_buffer);
             telemetry_byte_idx = 0;
         }
 
-        // Copy the next word into the transfer buffer.
-        transfer_buffer = telemetry_buffer[telemetry_byte_idx..telemetry_byte_idx+2].try_into().expect("Telemetry buffer failed to copy into SPI transfer buffer!");
+        if half_duplex {
+            // Stay silent on MOSI-only command frames; only drive MISO once asked for telemetry.
+            cs_pin.wait_for_falling_edge().await;
+            read(&mut transfer_buffer).await;
 
-        // Write that data, and read in the next command.
-        transfer_in_place(&mut transfer_buffer);
+            if !crc_ok(transfer_buffer) {
+                warn!("CRC command missmatch. Invalid command frame: {:08b}", transfer_buffer);
+                continue;
+            }
 
-        telemetry_byte_idx += 2;
+            if transfer_buffer[0] == TELEMETRY_REQUEST_OPCODE {
+                set_miso_spi();
+                let mut telemetry_word: [u8; 2] = telemetry_buffer[telemetry_byte_idx..telemetry_byte_idx + 2]
+                    .try_into()
+                    .expect("Telemetry buffer failed to copy into SPI transfer buffer!");
 
-        let computed_crc = StandardDShotVariant::compute_crc(u16::from_le_bytes(transfer_buffer));
-        let received_crc = transfer_buffer[1] & 0x0F;       
+                cs_pin.wait_for_falling_edge().await;
+                transfer_in_place(&mut telemetry_word).await;
+                telemetry_byte_idx += 2;
 
-        if computed_crc != received_crc {
-            warn!("CRC command missmatch. Expected {:04b}, got {:04b}. Invalid command frame: {:08b}", computed_crc, received_crc, transfer_buffer);
-            continue;
+                set_miso_hi_z();
+                continue;
+            }
+
+            info!("Read the following command: {:08b}", transfer_buffer);
+            write_dshot(&mut sms, transfer_buffer, config).await;
+
+            if crate::firmware_update::DFU_REQUESTED.swap(false, Ordering::Relaxed) {
+                run_dfu_session(&mut cs_pin, flash).await;
+            }
+        } else {
+            // Full duplex: telemetry streams out in lockstep with every incoming command frame.
+            transfer_buffer = telemetry_buffer[telemetry_byte_idx..telemetry_byte_idx + 2]
+                .try_into()
+                .expect("Telemetry buffer failed to copy into SPI transfer buffer!");
+
+            transfer_in_place(&mut transfer_buffer).await;
+            telemetry_byte_idx += 2;
+
+            if !crc_ok(transfer_buffer) {
+                warn!("CRC command missmatch. Invalid command frame: {:08b}", transfer_buffer);
+                continue;
+            }
+
+            info!("Read the following command: {:08b}", transfer_buffer);
+            write_dshot(&mut sms, transfer_buffer, config).await;
+
+            if crate::firmware_update::DFU_REQUESTED.swap(false, Ordering::Relaxed) {
+                run_dfu_session(&mut cs_pin, flash).await;
+            }
         }
+    }
+}
+
+/// Takes over the SPI exchange after the host sends [`crate::firmware_update::DFU_ENTER_OPCODE`].
+///
+/// The host first sends a little-endian `u32` byte count over two frames, then streams that
+/// many firmware bytes two at a time. Each chunk is buffered into the DFU partition via
+/// [`crate::firmware_update::FirmwareUpdate`]; once the declared byte count has arrived the
+/// image is checked back out, marked updated, and the MCU resets into the bootloader to
+/// perform the swap. A bad image rolls back automatically (see `main::run_boot_self_test`), so
+/// no separate abort command is needed on this link.
+async fn run_dfu_session(cs_pin: &mut Input<'static>, flash: &'static crate::firmware_update::SharedFlash) {
+    info!("Host requested firmware update, entering DFU mode");
+
+    let mut header = [0u8; 4];
+    cs_pin.wait_for_falling_edge().await;
+    read(&mut header[0..2]).await;
+    cs_pin.wait_for_falling_edge().await;
+    read(&mut header[2..4]).await;
+
+    let mut remaining = u32::from_le_bytes(header) as usize;
+    info!("Firmware update image size: {} bytes", remaining);
+
+    let mut update = crate::firmware_update::FirmwareUpdate::new(flash);
+    let mut chunk = [0u8; 2];
+
+    while remaining > 0 {
+        cs_pin.wait_for_falling_edge().await;
+        read(&mut chunk).await;
+
+        if let Err(err) = update.write_chunk(chunk) {
+            error!("Firmware update chunk write failed, aborting: {}", err);
+            return;
+        }
+
+        remaining = remaining.saturating_sub(chunk.len());
+    }
+
+    match update.finalize() {
+        Ok(()) => {
+            info!("Firmware update verified, resetting into bootloader");
+            cortex_m::peripheral::SCB::sys_reset();
+        }
+        Err(err) => error!("Firmware update verification failed, staying on current image: {}", err),
+    }
+}
+
+/// How often armed throttle targets are re-sent to the ESCs. DShot has no "hold last value"
+/// guarantee, so a throttle set once over I2C has to keep being retransmitted at the configured
+/// PIO update rate or the ESCs will fail safe and stop the motors.
+fn throttle_update_period() -> Duration {
+    Duration::from_micros(1_000_000 / u64::from(UPDATE_RATE_HZ))
+}
+
+/// Known pattern transmitted by [`spi_self_test_task`]; chosen to cover every bit transition
+/// a stuck-at or shorted pin would flip.
+#[cfg(feature = "spi-self-test")]
+const SELF_TEST_PATTERN: [u8; 4] = [0x00, 0xFF, 0xA5, 0x5A];
+
+/// Bench-only wiring check for [`config::spi::LoopbackPeripheral`]: clocks
+/// [`SELF_TEST_PATTERN`] out over DMA with the peripheral's own MOSI jumpered back to its own
+/// MISO, and confirms the bytes that come back match what went out. Run this once after
+/// flashing a new board before trusting [`spi_task`]'s DMA setup on the real command link,
+/// since both peripherals are wired up identically.
+#[cfg(feature = "spi-self-test")]
+#[embassy_executor::task]
+pub async fn spi_self_test_task(mut spi: embassy_rp::spi::Spi<'static, crate::config::spi::LoopbackPeripheral, embassy_rp::spi::Async>) {
+    let mut transfer_buf = SELF_TEST_PATTERN;
+
+    if let Err(err) = spi.transfer_in_place(&mut transfer_buf).await {
+        error!("SPI loopback self-test transfer failed: {}", err);
+        return;
+    }
+
+    if transfer_buf == SELF_TEST_PATTERN {
+        info!("SPI loopback self-test passed");
+    } else {
+        error!("SPI loopback self-test failed: sent {:02x}, got back {:02x}", SELF_TEST_PATTERN, transfer_buf);
+    }
+}
+
+/// Serves the board's command/register-map interface over the I2C slave link:
+///
+/// - A 1-byte write selects a [`TelemetryRegister`]; the following repeated-start read returns
+///   that field, decoded from the latest telemetry frame.
+/// - A 3-byte write to [`MOTOR_THROTTLE_BASE_ADDR`] + state-machine index (0-7) sets that
+///   motor's throttle target (little-endian, clamped to the configured calibration range).
+/// - A 2-byte write to [`ARM_REGISTER_ADDR`] arms (non-zero) or disarms (zero) the outputs.
+/// - A 2-byte write to [`CONFIG_REGISTER_ADDR`] saves or erases the persistent ESC config.
+/// - A 2-byte write to [`MIXER_ENABLE_REGISTER_ADDR`] switches the armed tick between raw
+///   per-motor throttles and [`crate::mixer`]'s 6-DOF mix of the axes below.
+/// - A 3-byte write to [`AXIS_BASE_ADDR`] + axis index (0-5) sets that axis of the command
+///   vector the mixer reads from (little-endian `i16`, scaled to `-1.0..=1.0`).
+///
+/// While armed, throttle targets (raw or mixer-derived, per [`MIXER_ENABLE_REGISTER_ADDR`]) are
+/// re-sent to the DShot outputs on a fixed tick; while disarmed, `MotorStop` is sent instead so a
+/// host that goes silent doesn't leave the ESCs spinning at the last throttle they were given.
+#[embassy_executor::task]
+pub async fn i2c_task(
+    mut i2c_device: I2cSlave<'static, I2C0>,
+    mut sms: SmDriverBatch,
+    config: &'static flash_config::Config,
+    flash: &'static crate::firmware_update::SharedFlash,
+) {
+    info!("Spawned core0 executor and i2c task!");
+
+    apply_spin_direction_config(&mut sms, config).await;
 
-        info!("Read the following command: {:08b}", transfer_buffer);
-        // Write the incoming DSHOT command.
-        write_dshot(&mut sms, transfer_buffer).await;
+    let mut request_buf = [0u8; 3];
+    let mut selected_register = TelemetryRegister::Temperature;
+    let mut armed = false;
+    let mut mixer_enabled = false;
+    let mut throttles = [0u16; 8];
+    let mut command_vector = CommandVector::ZERO;
+    let mut throttle_ticker = Ticker::every(throttle_update_period());
+
+    loop {
+        match select(i2c_device.listen(&mut request_buf), throttle_ticker.next()).await {
+            Either::First(Ok(I2cCommand::Write(1))) => {
+                match TelemetryRegister::from_addr(request_buf[0]) {
+                    Some(register) => selected_register = register,
+                    None => warn!("I2C host selected unknown telemetry register {}", request_buf[0]),
+                }
+            }
+            Either::First(Ok(I2cCommand::Write(2))) => {
+                if request_buf[0] == ARM_REGISTER_ADDR {
+                    armed = request_buf[1] != 0;
+                    info!("I2C host {} the thrusters", if armed { "armed" } else { "disarmed" });
+                } else if request_buf[0] == CONFIG_REGISTER_ADDR {
+                    match request_buf[1] {
+                        CONFIG_OP_SAVE => match flash_config::write_config(flash, *config) {
+                            Ok(()) => info!("I2C host saved ESC config to flash"),
+                            Err(_) => error!("Failed to save ESC config to flash"),
+                        },
+                        CONFIG_OP_ERASE => match flash_config::erase_config(flash) {
+                            Ok(()) => info!("I2C host erased persisted ESC config"),
+                            Err(_) => error!("Failed to erase persisted ESC config"),
+                        },
+                        op => warn!("I2C host wrote unknown config op {}", op),
+                    }
+                } else if request_buf[0] == MIXER_ENABLE_REGISTER_ADDR {
+                    let enable = request_buf[1] != 0;
+
+                    if enable && !mixer_enabled {
+                        for_each_driver_indexed!(sms, |driver, idx| {
+                            if idx < config.active_sm_count as usize {
+                                for _ in 0..6 {
+                                    driver.write_command(DShotCommand::ThreeDModeOn, true).await.unwrap_or_else(|err| {
+                                        error!("Error while enabling 3D mode for mixer control. Error: {}", err);
+                                    });
+                                }
+                            }
+                        });
+                    }
+
+                    mixer_enabled = enable;
+                    info!("I2C host {} mixer control", if mixer_enabled { "enabled" } else { "disabled" });
+                } else {
+                    warn!("I2C host wrote 2 bytes to unknown register {}", request_buf[0]);
+                }
+            }
+            Either::First(Ok(I2cCommand::Write(3))) => {
+                match motor_index_from_addr(request_buf[0]) {
+                    Some(idx) if idx < config.active_sm_count as usize => {
+                        let throttle = u16::from_le_bytes([request_buf[1], request_buf[2]])
+                            .clamp(config.throttle_calibration_min, config.throttle_calibration_max);
+                        throttles[idx] = throttle;
+                    }
+                    Some(idx) => warn!("I2C host wrote throttle for inactive state machine {}", idx),
+                    None => match axis_index_from_addr(request_buf[0]) {
+                        Some(axis_idx) => {
+                            let raw = i16::from_le_bytes([request_buf[1], request_buf[2]]);
+                            command_vector.set_axis(axis_idx, f32::from(raw) / f32::from(i16::MAX));
+                        }
+                        None => warn!("I2C host wrote 3 bytes to unknown register {}", request_buf[0]),
+                    },
+                }
+            }
+            Either::First(Ok(I2cCommand::Read)) => {
+                let mut telemetry_bytes = [0u8; crate::TELEMETRY_BUFFER_LEN];
+                TELEMETRY_BUFFERS.read(&mut telemetry_bytes);
+
+                let kiss_bytes: &[u8; 10] = telemetry_bytes[..10].try_into().unwrap();
+                let frame = TelemetryFrame::from_bytes(kiss_bytes);
+                let mut response = [0u8; 4];
+                let len = selected_register.encode(frame.as_ref(), &mut response);
+
+                if let Err(err) = i2c_device.respond_to_read(&response[..len.max(1)]).await {
+                    error!("Error while responding to I2C read. Error: {}", err);
+                }
+            }
+            Either::First(Ok(_)) => {
+                // Writes to other lengths (e.g. general call) aren't part of the register map.
+            }
+            Either::First(Err(err)) => error!("I2C slave error: {}", err),
+            Either::Second(()) => {
+                if armed && mixer_enabled {
+                    let mixed = mixer::mix_to_throttles(command_vector, &mixer::DEFAULT_ALLOCATION_MATRIX);
+                    write_mixed_throttles(&mut sms, mixed, config).await;
+                } else if armed {
+                    for_each_driver_indexed!(sms, |driver, idx| {
+                        if idx < config.active_sm_count as usize {
+                            driver.write_throttle(throttles[idx], true).await.unwrap_or_else(|err| {
+                                error!("Error while writing I2C-commanded throttle to PIOs. Error: {}", err);
+                            });
+                        }
+                    });
+                } else {
+                    for_each_driver_indexed!(sms, |driver, idx| {
+                        if idx < config.active_sm_count as usize {
+                            driver.write_command(DShotCommand::MotorStop, true).await.unwrap_or_else(|err| {
+                                error!("Error while writing disarm command to PIOs. Error: {}", err);
+                            });
+                        }
+                    });
+                }
+            }
+        }
     }
 }
 
+/// Exchanges `transfer_buf` with the SPI master, in place, over the SSP FIFOs.
+///
+/// Behind the `spi-polling` feature this busy-waits the CPU one byte at a time; otherwise it's
+/// backed by a paired DMA transfer (see [`transfer_in_place_dma`]) that frees core0 to keep
+/// servicing the DShot state machines while the master clocks the frame.
+#[cfg(not(feature = "spi-polling"))]
+async fn transfer_in_place(transfer_buf: &mut [u8]) {
+    transfer_in_place_dma(transfer_buf).await;
+}
+
+#[cfg(feature = "spi-polling")]
 #[allow(clippy::cast_possible_truncation)]
-fn transfer_in_place(transfer_buf: &mut [u8]) {
+async fn transfer_in_place(transfer_buf: &mut [u8]) {
     for byte in transfer_buf {
         while tx_fifo_is_full() {} // Wait until tx FIFO is empty
         set_fifo(u16::from(*byte));
@@ -157,8 +648,15 @@ fn transfer_in_place(transfer_buf: &mut [u8]) {
     flush();
 }
 
+/// Clocks `read_buf.len()` words in while transmitting zeroes, discarding what the master sent.
+#[cfg(not(feature = "spi-polling"))]
+async fn read(read_buf: &mut [u8]) {
+    read_dma(read_buf).await;
+}
+
+#[cfg(feature = "spi-polling")]
 #[allow(clippy::cast_possible_truncation)]
-fn read(read_buf: &mut [u8]) {
+async fn read(read_buf: &mut [u8]) {
     for byte in read_buf {
         while tx_fifo_is_full() {} // Wait until tx FIFO is empty
         set_fifo(0);
@@ -168,28 +666,157 @@ fn read(read_buf: &mut [u8]) {
     flush();
 }
 
-fn wait_one_transmission() {
+/// Clocks a single throwaway word, used to force a transmission while waiting to resync.
+#[cfg(not(feature = "spi-polling"))]
+async fn wait_one_transmission() {
+    let mut scratch = [0u8; 1];
+    read_dma(&mut scratch).await;
+}
+
+#[cfg(feature = "spi-polling")]
+async fn wait_one_transmission() {
     while tx_fifo_is_full() {} // Wait until tx FIFO is empty
     set_fifo(0);
     while rx_fifo_is_empty() {} // Wait until rx FIFO is full
     flush();
 }
 
+// RP2040 datasheet table 2.5.3: DMA data request numbers wired to the SSP0 (SPI0) FIFOs.
+#[cfg(not(feature = "spi-polling"))]
+const DREQ_SPI0_TX: u8 = 16;
+#[cfg(not(feature = "spi-polling"))]
+const DREQ_SPI0_RX: u8 = 17;
+
+// Channel numbers mirror `config::spi::{RxDmaChannel, TxDmaChannel}` (`DMA_CH2`/`DMA_CH3`).
+// This module talks to the SSP through the PAC directly rather than through embassy's `Spi`
+// driver, which has no slave-mode support, so the DMA channels are driven the same way.
+#[cfg(not(feature = "spi-polling"))]
+const RX_DMA_CHANNEL: usize = 2;
+#[cfg(not(feature = "spi-polling"))]
+const TX_DMA_CHANNEL: usize = 3;
+
+/// Shuttles `transfer_buf` through the SSP FIFOs via a paired RX+TX DMA transfer instead of
+/// polling `SSPSR` for every byte. The TX channel clocks `transfer_buf`'s current contents out
+/// while the RX channel overwrites it in place with whatever the master sends back.
+#[cfg(not(feature = "spi-polling"))]
+async fn transfer_in_place_dma(transfer_buf: &mut [u8]) {
+    dma_transfer(transfer_buf.as_ptr() as u32, true, transfer_buf).await;
+}
+
+/// Like [`transfer_in_place_dma`], but transmits zeroes instead of `read_buf`'s contents.
+#[cfg(not(feature = "spi-polling"))]
+async fn read_dma(read_buf: &mut [u8]) {
+    static ZERO: u8 = 0;
+    dma_transfer(core::ptr::addr_of!(ZERO) as u32, false, read_buf).await;
+}
+
+#[cfg(not(feature = "spi-polling"))]
+async fn dma_transfer(tx_read_addr: u32, tx_incr_read: bool, rx_buf: &mut [u8]) {
+    use pac::dma::vals::{DataSize, TreqSel};
+
+    pac::SPI0.dmacr().modify(|w| {
+        w.set_txdmae(true);
+        w.set_rxdmae(true);
+    });
+
+    let rx = pac::DMA.ch(RX_DMA_CHANNEL);
+    let tx = pac::DMA.ch(TX_DMA_CHANNEL);
+
+    rx.write_addr().write_value(rx_buf.as_mut_ptr() as u32);
+    rx.read_addr().write_value(pac::SPI0.dr().as_ptr() as u32);
+    rx.trans_count().write_value(rx_buf.len() as u32);
+    rx.ctrl_trig().write(|w| {
+        w.set_data_size(DataSize::SIZE_BYTE);
+        w.set_incr_read(false);
+        w.set_incr_write(true);
+        w.set_treq_sel(TreqSel::from_bits(DREQ_SPI0_RX));
+        w.set_en(true);
+    });
+
+    tx.read_addr().write_value(tx_read_addr);
+    tx.write_addr().write_value(pac::SPI0.dr().as_ptr() as u32);
+    tx.trans_count().write_value(rx_buf.len() as u32);
+    tx.ctrl_trig().write(|w| {
+        w.set_data_size(DataSize::SIZE_BYTE);
+        w.set_incr_read(tx_incr_read);
+        w.set_incr_write(false);
+        w.set_treq_sel(TreqSel::from_bits(DREQ_SPI0_TX));
+        w.set_en(true);
+    });
+
+    // Yield to the executor between polls rather than busy-looping the CPU, so core0 can keep
+    // servicing the DShot state machines while the SPI master clocks the frame.
+    while rx.ctrl_trig().read().busy() || tx.ctrl_trig().read().busy() {
+        yield_now().await;
+    }
+
+    flush();
+}
+
 #[inline(always)]
+#[cfg(feature = "spi-polling")]
 fn tx_fifo_is_full() -> bool {
     !pac::SPI0.sr().read().tnf()
 }
 
 #[inline(always)]
+#[cfg(feature = "spi-polling")]
 fn rx_fifo_is_empty() -> bool {
     !pac::SPI0.sr().read().rne()
 }
 
 #[inline(always)]
+#[cfg(feature = "spi-polling")]
 fn set_fifo(data: u16) {
     pac::SPI0.dr().write(|w| w.set_data(data));
 }
 
+// GPIO numbers wired to CLK/MOSI/MISO by `config::spi` (`PIN_2`/`PIN_3`/`PIN_4`, the first
+// variant of each of `ClkPin`/`MosiPin`/`MisoPin`).
+const CLK_PIN_NUM: usize = 2;
+const MOSI_PIN_NUM: usize = 3;
+const MISO_PIN_NUM: usize = 4;
+
+/// Brings SSP0 (SPI0) out of reset, configures it as an 8-bit Motorola-format SPI slave
+/// matching `config::spi::new()`'s phase/polarity, and switches CLK/MOSI/MISO to the SSP
+/// alternate function. Must run once, before [`spi_task`]'s sync loop starts clocking it.
+///
+/// CS is deliberately left a plain GPIO input (funcsel left at its SIO reset default): this
+/// protocol reads CS edges in software (`cs_pin.wait_for_falling_edge`) rather than wiring it
+/// to the SSP's hardware frame-select, so [`spi_task`] can resync on a lost/extra edge instead
+/// of trusting the SSP to always agree with the host on where a frame starts.
+fn init_ssp0_slave() {
+    pac::RESETS.reset().modify(|w| w.set_spi0(false));
+    while !pac::RESETS.reset_done().read().spi0() {}
+
+    pac::SPI0.cr0().modify(|w| {
+        w.set_dss(0x7); // 8-bit data size (DSS is encoded as size - 1)
+        w.set_spo(crate::config::spi::new().polarity == embassy_rp::spi::Polarity::IdleHigh);
+        w.set_sph(crate::config::spi::new().phase == embassy_rp::spi::Phase::CaptureOnSecondTransition);
+    });
+
+    pac::SPI0.cr1().modify(|w| {
+        w.set_ms(true); // slave mode
+        w.set_sse(true); // enable
+    });
+
+    pac::IO_BANK0.gpio(CLK_PIN_NUM).ctrl().modify(|w| w.set_funcsel(crate::config::spi::SPI_FUNCSEL));
+    pac::IO_BANK0.gpio(MOSI_PIN_NUM).ctrl().modify(|w| w.set_funcsel(crate::config::spi::SPI_FUNCSEL));
+    set_miso_spi();
+}
+
+/// Tri-states the MISO pin by handing it to the SIO peripheral as a floating input, so the
+/// slave stays electrically silent on MOSI-only command frames in half-duplex mode.
+fn set_miso_hi_z() {
+    pac::SIO.gpio_oe_clr().write_value(1 << MISO_PIN_NUM);
+    pac::IO_BANK0.gpio(MISO_PIN_NUM).ctrl().modify(|w| w.set_funcsel(5)); // SIO
+}
+
+/// Hands MISO back to the SSP peripheral so the slave can drive telemetry onto the bus.
+fn set_miso_spi() {
+    pac::IO_BANK0.gpio(MISO_PIN_NUM).ctrl().modify(|w| w.set_funcsel(crate::config::spi::SPI_FUNCSEL));
+}
+
 #[inline(always)]
 fn get_fifo_data() -> u16 {
     pac::SPI0.dr().read().data()
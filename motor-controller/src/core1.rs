@@ -1,93 +1,185 @@
-use embassy_rp::uart::{self, UartRx};
+use embassy_rp::uart::{self, Async, UartRx};
+use embassy_futures::select::{Either, select};
+use embassy_time::{Duration, Timer};
+use core::sync::atomic::Ordering;
 use defmt::{error, info, warn};
-use rp2040_dshot::encoder::TelemetryFrame;
-use crate::TELEMETRY_BUFFERS;
-
-#[derive(Clone, Copy, PartialEq)]
-enum CrcState {
-    /// The latest CRC checksum was valid.
-    Valid,
-    /// The latest CRC checksum was invalid
-    Invalid
+use rp2040_dshot::encoder::{TelemetryAssembler, TelemetryFrame};
+use crate::{TELEMETRY_BUFFER_LEN, TELEMETRY_BUFFERS, TELEMETRY_LINK_STATS};
+
+#[cfg(feature = "bidirectional-dshot")]
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+#[cfg(feature = "bidirectional-dshot")]
+use embassy_sync::channel::Channel;
+#[cfg(feature = "bidirectional-dshot")]
+use embassy_time::Ticker;
+#[cfg(feature = "bidirectional-dshot")]
+use rp2040_dshot::encoder::{ERpmVarient, StandardERpmFrame};
+
+/// Idle gap used to delimit KISS telemetry frames.
+///
+/// KISS ESCs send one 10-byte frame back-to-back and then leave the line idle until the
+/// next frame, so roughly two character times of silence at 115200 8N1 (~174us) reliably
+/// marks a frame boundary.
+const FRAME_IDLE_GAP: Duration = Duration::from_micros(174);
+
+/// Busy-waits in RAM while core0 holds [`crate::CORE1_FLASH_LOCKOUT`] for a flash erase/program.
+///
+/// Must live outside flash (`.data.ram_func`) and call nothing flash-resident: the RP2040's XIP
+/// cache cannot serve code to either core while core0 is mid-erase/program
+/// (`flash_config::with_core1_parked`), so core1 must not fetch a single instruction from flash
+/// for the duration. Acks via [`crate::CORE1_PARKED`] so core0 knows it's safe to proceed.
+#[link_section = ".data.ram_func"]
+#[inline(never)]
+fn park_for_flash_access() {
+    crate::CORE1_PARKED.store(true, Ordering::Release);
+    while crate::CORE1_FLASH_LOCKOUT.load(Ordering::Acquire) {
+        core::hint::spin_loop();
+    }
+    crate::CORE1_PARKED.store(false, Ordering::Release);
 }
 
-macro_rules! impl_dshot_telemetry_task {
-    ($mode:ty, $read_fn:ident) => {
-        #[embassy_executor::task]
-        pub async fn dshot_telemetry_task(mut uart: UartRx<'static, $mode>) {
-            info!("Spawned Core1 and telemetry executory!");
-            
-            let mut internal_buf = [0u8; 10];
-            let mut crc_state = CrcState::Valid;
-
-            info!("Reading DShot Telemetry...");
-            loop {
-                if let Err(read_error) = $read_fn(&mut uart, &mut internal_buf).await {
-                    handle_uart_error(read_error);
-                    continue;
-                }
-
-                // info!("Telemetry data: {:?}", internal_buf);
-
-                let computed_crc = TelemetryFrame::compute_crc(&internal_buf[..9]);
-                let received_crc = internal_buf[9];
-
-                if internal_buf[9] != computed_crc {
-                    warn!("Telemetry CRC mismatch! Expected {:08b}, got {:08b}. Attempting shift by one! Invalid telemetry frame: {}", computed_crc, received_crc, internal_buf);
-
-                    let mut single_byte = [0u8; 1];
-                    if let Err(read_error) = $read_fn(&mut uart, &mut single_byte).await {
-                        handle_uart_error(read_error);
-                    }
-
-                    crc_state = CrcState::Invalid;
-                    continue;
-                }
-                
-                if crc_state == CrcState::Invalid {
-                    info!("Success! Valid telemetry frame: {:?}", internal_buf);
-                    crc_state = CrcState::Valid;
-                }
-
-                TELEMETRY_BUFFERS.write(&mut internal_buf);
-                // info!("Wrote {:?} into telemetry buffer!", internal_buf);
-            }
+#[embassy_executor::task]
+pub async fn dshot_telemetry_task(mut uart: UartRx<'static, Async>) {
+    info!("Spawned Core1 and telemetry executory!");
+
+    let mut assembler = TelemetryAssembler::new();
+
+    info!("Reading DShot Telemetry...");
+    loop {
+        if crate::CORE1_FLASH_LOCKOUT.load(Ordering::Acquire) {
+            park_for_flash_access();
         }
-    };
+
+        read_until_idle(&mut uart, &mut assembler).await;
+
+        let len = assembler.buffered_len();
+
+        // Nothing arrived before the line went idle again; still between frames.
+        if len == 0 {
+            continue;
+        }
+
+        // Snapshot the raw bytes before `reset` clears the assembler, so a short or
+        // CRC-invalid frame can still be logged and a good one forwarded verbatim.
+        let mut raw = [0u8; 10];
+        raw[..len].copy_from_slice(assembler.buffered_bytes());
+        let decoded = assembler.reset();
+
+        if len != raw.len() {
+            warn!("Telemetry frame mis-sized: {} bytes before idle, expected 10. Discarding and resyncing.", len);
+            // Short relative to wiring issues (e.g. a loose connection), as opposed to a
+            // complete-but-corrupt frame, which points at line noise instead.
+            TELEMETRY_LINK_STATS.short_reads.fetch_add(1, Ordering::Relaxed);
+            TELEMETRY_LINK_STATS.record_bad_frame();
+            continue;
+        }
+
+        if decoded.is_none() {
+            warn!("Telemetry CRC mismatch. Invalid telemetry frame: {}", raw);
+            TELEMETRY_LINK_STATS.crc_failures.fetch_add(1, Ordering::Relaxed);
+            TELEMETRY_LINK_STATS.record_bad_frame();
+            continue;
+        }
+
+        // `TELEMETRY_BUFFERS` is sized for the wider BDDShot layout too; pad the rest with
+        // zeroes since only the first 10 bytes are meaningful here (see `TelemetryFrame`).
+        let mut frame = [0u8; TELEMETRY_BUFFER_LEN];
+        frame[..raw.len()].copy_from_slice(&raw);
+        TELEMETRY_BUFFERS.write(&mut frame);
+        TELEMETRY_LINK_STATS.record_good_frame();
+        // info!("Wrote {:?} into telemetry buffer!", raw);
+    }
 }
 
-#[cfg(not(feature = "dummy-telemetry"))]
-impl_dshot_telemetry_task!(uart::Blocking, blocking_read_async);
+/// How often the aggregated BDDShot telemetry frame is rebuilt and published to
+/// [`TELEMETRY_BUFFERS`]. Independent of the DShot command rate; telemetry doesn't need to be
+/// fresher than a host can usefully poll it over SPI/I2C.
+#[cfg(feature = "bidirectional-dshot")]
+const BD_DSHOT_TELEMETRY_PERIOD: Duration = Duration::from_millis(20);
+
+/// Byte offset of the per-motor eRPM fields within the BDDShot telemetry frame.
+#[cfg(feature = "bidirectional-dshot")]
+const RPM_FIELD_OFFSET: usize = 0;
+/// Byte offset of the validity bitmask (bit N set if state machine N's last sample decoded
+/// cleanly) within the BDDShot telemetry frame.
+#[cfg(feature = "bidirectional-dshot")]
+const VALIDITY_FIELD_OFFSET: usize = 16;
+
+/// Aggregates the latest decoded eRPM sample from each of the eight state machines' telemetry
+/// channels (fed by `rp2040_dshot::driver::erpm_reader_task_impl`) into one structured frame
+/// and publishes it to [`TELEMETRY_BUFFERS`] on a fixed cadence.
+///
+/// Layout (18 bytes, little-endian): 8 x `u16` mechanical RPM (one per state machine, in PIO0
+/// SM0-3 then PIO1 SM0-3 order), then a validity bitmask byte (bit N clear means state machine
+/// N had no fresh sample this period, so its RPM field holds the last known value), then one
+/// reserved byte.
+#[cfg(feature = "bidirectional-dshot")]
+#[embassy_executor::task]
+pub async fn bd_dshot_telemetry_task(channels: [&'static Channel<NoopRawMutex, u16, 3>; 8]) {
+    info!("Spawned BDDShot telemetry aggregation task!");
+
+    let mut frame = [0u8; TELEMETRY_BUFFER_LEN];
+    let mut ticker = Ticker::every(BD_DSHOT_TELEMETRY_PERIOD);
 
-#[cfg(feature = "dummy-telemetry")]
-impl_dshot_telemetry_task!(uart::Async, async_read_async);
-
-// Small async wrappers to allow for macro definition 
-// (maybe more code than copy+pasting dshot telemetry task at this point, but it does have a centralizing advantage)
-// always inlined so should be 0 overhead.
-#[cfg(not(feature = "dummy-telemetry"))]
-#[inline(always)]
-async fn blocking_read_async(
-    uart: &mut UartRx<'static, uart::Blocking>, 
-    buffer: &mut [u8]
-) -> Result<(), uart::Error> {
-    uart.blocking_read(buffer)
+    loop {
+        ticker.next().await;
+
+        if crate::CORE1_FLASH_LOCKOUT.load(Ordering::Acquire) {
+            park_for_flash_access();
+        }
+
+        let mut valid_mask: u8 = 0;
+
+        for (idx, channel) in channels.iter().enumerate() {
+            let Ok(raw) = channel.try_receive() else {
+                continue;
+            };
+
+            let Some(erpm_frame) = StandardERpmFrame::from_raw(raw) else {
+                warn!("BDDShot telemetry CRC mismatch on state machine {}", idx);
+                continue;
+            };
+
+            #[allow(clippy::cast_possible_truncation)]
+            let rpm = erpm_frame
+                .mechanical_rpm(crate::config::dshot::MOTOR_CONFIG)
+                .min(u32::from(u16::MAX)) as u16;
+            let field = RPM_FIELD_OFFSET + idx * 2;
+            frame[field..field + 2].copy_from_slice(&rpm.to_le_bytes());
+            valid_mask |= 1 << idx;
+        }
+
+        frame[VALIDITY_FIELD_OFFSET] = valid_mask;
+        TELEMETRY_BUFFERS.write(&mut frame);
+    }
 }
 
-#[cfg(feature = "dummy-telemetry")]
-#[inline(always)]
-async fn async_read_async(
-    uart: &mut UartRx<'static, uart::Async>,
-    buf: &mut [u8],
-) -> Result<(), uart::Error> {
-    uart.read(buf).await
+/// Feeds bytes into `assembler` one at a time until the RX line has been silent for
+/// [`FRAME_IDLE_GAP`], treating that idle gap as a frame delimiter.
+///
+/// Stops once `assembler` has buffered a full frame's worth of bytes or the idle gap is
+/// observed, whichever comes first; the caller reads the result back off `assembler` and calls
+/// [`TelemetryAssembler::reset`] to decode it and resynchronise for the next frame.
+async fn read_until_idle(uart: &mut UartRx<'static, Async>, assembler: &mut TelemetryAssembler) {
+    let mut byte = [0u8; 1];
+
+    while assembler.buffered_len() < 10 {
+        match select(uart.read(&mut byte), Timer::after(FRAME_IDLE_GAP)).await {
+            Either::First(Ok(())) => assembler.feed(&byte),
+            Either::First(Err(read_error)) => {
+                record_rx_error_stats(&read_error);
+                handle_uart_error(read_error);
+                break;
+            }
+            Either::Second(()) => break,
+        }
+    }
 }
 
 #[cfg(feature = "dummy-telemetry")]
 #[embassy_executor::task]
 pub async fn dummy_telemetry_writer(mut tx: uart::UartTx<'static, uart::Async>) {
-    use rp2040_dshot::encoder::TelemetryFrame;
-    use embassy_time::{Ticker, Duration};
+    use embassy_time::Ticker;
 
     let mut data = [1u8, 2, 3, 4, 5, 6, 7, 8, 9, 0];
     let crc = TelemetryFrame::compute_crc(&data[..9]);
@@ -110,6 +202,18 @@ pub async fn dummy_telemetry_writer(mut tx: uart::UartTx<'static, uart::Async>)
     }
 }
 
+/// Updates the link-health counters for an RX-side UART error (see [`TELEMETRY_LINK_STATS`]).
+fn record_rx_error_stats(err: &uart::Error) {
+    match err {
+        uart::Error::Overrun => TELEMETRY_LINK_STATS.overruns.fetch_add(1, Ordering::Relaxed),
+        uart::Error::Break => TELEMETRY_LINK_STATS.breaks.fetch_add(1, Ordering::Relaxed),
+        uart::Error::Framing => TELEMETRY_LINK_STATS.framing_errors.fetch_add(1, Ordering::Relaxed),
+        uart::Error::Parity => TELEMETRY_LINK_STATS.parity_errors.fetch_add(1, Ordering::Relaxed),
+        _ => 0,
+    };
+    TELEMETRY_LINK_STATS.record_bad_frame();
+}
+
 fn handle_uart_error(err: uart::Error) {
     match err {
         uart::Error::Overrun => error!("UART telemetry FIFO or shift-register overflowed!"),
@@ -119,4 +223,4 @@ fn handle_uart_error(err: uart::Error) {
         uart::Error::Parity => error!("UART telemetry packet parity detected error!"),
         _ => error!("Unknown UART telemetry error!")
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,133 @@
+//! In-field firmware update over the SPI command link.
+//!
+//! The host enters DFU mode by sending [`DFU_ENTER_OPCODE`] as a command frame (see
+//! `core0::write_dshot`), then streams the new image in over subsequent SPI frames
+//! (`core0::run_dfu_session` drives the exchange). Chunks are buffered a flash page at a time
+//! and written into the inactive DFU partition through `embassy-boot-rp`'s
+//! [`BlockingFirmwareUpdater`]. Once the whole image has arrived, [`FirmwareUpdate::finalize`]
+//! checks the updater's state, marks the image updated, and a plain system reset hands control
+//! to the first-stage bootloader to perform the swap.
+//!
+//! A bad image rolls back automatically: the bootloader only treats the new image as good once
+//! [`confirm_boot`] calls `mark_booted`, which `main` only does after the freshly booted image
+//! passes its own self-test (all eight state machines up, at least one ESC telemetry frame
+//! decoded).
+
+use core::cell::RefCell;
+use core::ptr::addr_of_mut;
+use core::sync::atomic::AtomicBool;
+
+use defmt::{error, info};
+use embassy_boot_rp::{AlignedBuffer, BlockingFirmwareUpdater, FirmwareUpdaterConfig};
+use embassy_rp::flash::{Blocking, Flash};
+use embassy_rp::peripherals::FLASH;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+
+use crate::flash_config::FLASH_SIZE;
+
+/// Reserved command-link opcode (see `core0::write_dshot`) that hands the SPI exchange over to
+/// `core0::run_dfu_session` instead of driving the ESCs. `DShotCommand` only occupies 0-47 and
+/// `core0::TELEMETRY_REQUEST_OPCODE` claims `0xFF`, so `0xFE` can never collide with either.
+pub const DFU_ENTER_OPCODE: u8 = 0xFE;
+
+/// Set by `core0::write_dshot` when it sees [`DFU_ENTER_OPCODE`]; consumed by `core0::spi_task`
+/// once the command frame that carried it has finished its SPI exchange.
+pub static DFU_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// One RP2040 flash page; `write_firmware`'s minimum write granularity.
+const PAGE_LEN: usize = 256;
+
+/// The physical flash instance, shared between `main`'s boot-time config read and any DFU
+/// session, since the RP2040 only exposes one `FLASH` peripheral to claim.
+pub type SharedFlash = Mutex<NoopRawMutex, RefCell<Flash<'static, FLASH, Blocking, FLASH_SIZE>>>;
+
+/// Scratch page `BlockingFirmwareUpdater` stages a write through. A DFU session and the
+/// boot-time [`confirm_boot`] call never run concurrently, so reusing one static buffer is
+/// safe the same way `main::CORE1_STACK` is: only one `&mut` is ever live at a time.
+static mut UPDATE_SCRATCH: AlignedBuffer<PAGE_LEN> = AlignedBuffer([0; PAGE_LEN]);
+
+/// Buffers incoming SPI chunks a page at a time and drives them into the DFU partition that
+/// `embassy-boot-rp` locates from the linker script.
+pub struct FirmwareUpdate {
+    updater: BlockingFirmwareUpdater<'static, Flash<'static, FLASH, Blocking, FLASH_SIZE>, Flash<'static, FLASH, Blocking, FLASH_SIZE>>,
+    page: [u8; PAGE_LEN],
+    filled: usize,
+    offset: usize,
+}
+
+impl FirmwareUpdate {
+    /// Starts a new update session against `flash`, the same instance `main` reads
+    /// [`crate::flash_config::Config`] from at boot.
+    pub fn new(flash: &'static SharedFlash) -> Self {
+        let config = FirmwareUpdaterConfig::from_linkerfile_blocking(flash, flash);
+        let scratch = unsafe { &mut *addr_of_mut!(UPDATE_SCRATCH) };
+
+        Self {
+            updater: BlockingFirmwareUpdater::new(config, scratch),
+            page: [0; PAGE_LEN],
+            filled: 0,
+            offset: 0,
+        }
+    }
+
+    /// Buffers one SPI frame's worth of firmware bytes, flushing a full page to flash once
+    /// enough have accumulated.
+    pub fn write_chunk(&mut self, chunk: [u8; 2]) -> Result<(), rp2040_dshot::Error> {
+        self.page[self.filled..self.filled + chunk.len()].copy_from_slice(&chunk);
+        self.filled += chunk.len();
+
+        if self.filled == PAGE_LEN {
+            self.flush_page()?;
+        }
+
+        Ok(())
+    }
+
+    /// Flushes any partial final page, then checks the image back out and marks it updated so
+    /// the bootloader performs the swap on the next reset.
+    pub fn finalize(mut self) -> Result<(), rp2040_dshot::Error> {
+        self.flush_page()?;
+
+        self.updater.get_state().map_err(|_| rp2040_dshot::Error::UpdateVerifyFailed)?;
+        self.updater.mark_updated().map_err(|_| rp2040_dshot::Error::UpdateVerifyFailed)
+    }
+
+    /// Pads the accumulated bytes out to a full page (firmware images rarely land on an exact
+    /// page boundary) and writes them at the current offset.
+    fn flush_page(&mut self) -> Result<(), rp2040_dshot::Error> {
+        if self.filled == 0 {
+            return Ok(());
+        }
+
+        self.page[self.filled..].fill(0xFF);
+
+        self.updater
+            .write_firmware(self.offset, &self.page)
+            .map_err(|_| rp2040_dshot::Error::FirmwareWriteError)?;
+
+        self.offset += PAGE_LEN;
+        self.filled = 0;
+        Ok(())
+    }
+}
+
+/// Runs after `main` has brought up all eight DShot state machines and given the telemetry
+/// link a chance to decode a frame. If `self_test_passed`, marks the running image booted so
+/// `embassy-boot-rp` stops treating it as provisional; otherwise leaves it unconfirmed so the
+/// bootloader rolls back to the last known-good image on the next reset.
+pub fn confirm_boot(flash: &'static SharedFlash, self_test_passed: bool) {
+    if !self_test_passed {
+        error!("Boot self-test failed; leaving firmware image unconfirmed");
+        return;
+    }
+
+    let config = FirmwareUpdaterConfig::from_linkerfile_blocking(flash, flash);
+    let scratch = unsafe { &mut *addr_of_mut!(UPDATE_SCRATCH) };
+    let mut updater = BlockingFirmwareUpdater::new(config, scratch);
+
+    match updater.mark_booted() {
+        Ok(()) => info!("Boot self-test passed; firmware image confirmed"),
+        Err(_) => error!("Failed to mark firmware image booted"),
+    }
+}
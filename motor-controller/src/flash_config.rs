@@ -0,0 +1,212 @@
+//! Flash-backed persistent configuration store for DShot/ESC parameters.
+//!
+//! Values live in two 4 KB sectors (A/B) carved out of the top of flash so that a write is
+//! power-safe: the new record is programmed into the unused slot under a higher sequence
+//! number, and only afterwards is the old slot erased. On boot, [`read_config`] picks whichever
+//! slot has the highest sequence number and a valid CRC; if neither does, it falls back to
+//! [`Config::DEFAULT`].
+
+use core::sync::atomic::Ordering;
+
+use embassy_rp::flash::{Blocking, Error, Flash};
+use embassy_rp::peripherals::FLASH;
+
+use crate::firmware_update::SharedFlash;
+
+/// On-chip flash size for the RP2040 modules this firmware targets.
+pub const FLASH_SIZE: usize = 2 * 1024 * 1024;
+
+const SECTOR_SIZE: u32 = 4096;
+const SLOT_A_OFFSET: u32 = FLASH_SIZE as u32 - 2 * SECTOR_SIZE;
+const SLOT_B_OFFSET: u32 = FLASH_SIZE as u32 - SECTOR_SIZE;
+
+/// Record layout: 4-byte sequence number, the serialized [`Config`] fields, then a 4-byte CRC32
+/// of everything before it. Padded out to one flash page (256 bytes) since `blocking_write`
+/// requires page-aligned, page-sized writes.
+const RECORD_LEN: usize = 15;
+const PAGE_LEN: usize = 256;
+
+/// DShot/ESC parameters a host can reconfigure without a recompile.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// `DShotSpeed` discriminant (see `rp2040_dshot::encoder::DShotSpeed`). Stored and persisted
+    /// only; the PIO timing is built from `config::dshot::DSHOT_SPEED` at compile time.
+    pub dshot_speed: u8,
+    /// Whether the bidirectional (BDShot) PIO program is selected. Stored and persisted only; the
+    /// program actually loaded is chosen at build time by the `bidirectional-dshot` feature.
+    pub bidirectional: bool,
+    /// Number of state machines actively driving ESCs, 1-8.
+    pub active_sm_count: u8,
+    /// One bit per motor; set to reverse that motor's spin direction.
+    pub spin_direction_reversed: u8,
+    /// Throttle value for arming. Stored and persisted only; no task writes it to the ESCs.
+    pub arm_throttle: u16,
+    /// Raw throttle value calibrated to an ESC's lowest stop.
+    pub throttle_calibration_min: u16,
+    /// Raw throttle value calibrated to an ESC's highest stop.
+    pub throttle_calibration_max: u16,
+    /// Telemetry frame layout identifier. Stored and persisted only; `core0::i2c_task` and
+    /// `core0::spi_task` serve whatever layout the `bidirectional-dshot` feature selects.
+    pub telemetry_frame_layout: u8,
+}
+
+impl Config {
+    /// Sane defaults matching `config::dshot`'s compiled-in values, used until a host writes one.
+    pub const DEFAULT: Self = Self {
+        dshot_speed: 1, // DShotSpeed::DShot300
+        bidirectional: false,
+        active_sm_count: 8,
+        spin_direction_reversed: 0,
+        arm_throttle: 0,
+        throttle_calibration_min: 48,
+        throttle_calibration_max: 2047,
+        telemetry_frame_layout: 0, // KISS
+    };
+
+    fn to_bytes(self) -> [u8; RECORD_LEN] {
+        let mut out = [0u8; RECORD_LEN];
+        out[0] = self.dshot_speed;
+        out[1] = u8::from(self.bidirectional);
+        out[2] = self.active_sm_count;
+        out[3] = self.spin_direction_reversed;
+        out[4..6].copy_from_slice(&self.arm_throttle.to_le_bytes());
+        out[6..8].copy_from_slice(&self.throttle_calibration_min.to_le_bytes());
+        out[8..10].copy_from_slice(&self.throttle_calibration_max.to_le_bytes());
+        out[10] = self.telemetry_frame_layout;
+        out
+    }
+
+    fn from_bytes(bytes: &[u8; RECORD_LEN]) -> Self {
+        Self {
+            dshot_speed: bytes[0],
+            bidirectional: bytes[1] != 0,
+            active_sm_count: bytes[2],
+            spin_direction_reversed: bytes[3],
+            arm_throttle: u16::from_le_bytes([bytes[4], bytes[5]]),
+            throttle_calibration_min: u16::from_le_bytes([bytes[6], bytes[7]]),
+            throttle_calibration_max: u16::from_le_bytes([bytes[8], bytes[9]]),
+            telemetry_frame_layout: bytes[10],
+        }
+    }
+}
+
+/// Reads whichever of the two slots holds the newest valid record, falling back to
+/// [`Config::DEFAULT`] if both slots are blank or corrupt.
+pub fn read_config(flash: &mut Flash<'_, FLASH, Blocking, FLASH_SIZE>) -> Config {
+    let a = read_slot(flash, SLOT_A_OFFSET);
+    let b = read_slot(flash, SLOT_B_OFFSET);
+
+    match (a, b) {
+        (Some((seq_a, config_a)), Some((seq_b, config_b))) => {
+            if seq_b > seq_a { config_b } else { config_a }
+        }
+        (Some((_, config)), None) | (None, Some((_, config))) => config,
+        (None, None) => Config::DEFAULT,
+    }
+}
+
+/// Programs `config` into whichever slot is currently stale, then erases the other one.
+///
+/// Writing the new record before erasing the old one means a power loss at any point still
+/// leaves one of the two slots holding a valid, CRC-checked record.
+///
+/// Parks core1 for the duration (see [`with_core1_parked`]): the RP2040 can't serve flash reads,
+/// including instruction fetches, to either core while one is mid-erase/program.
+pub fn write_config(flash: &'static SharedFlash, config: Config) -> Result<(), Error> {
+    with_core1_parked(|| {
+        flash.lock(|cell| {
+            let mut flash = cell.borrow_mut();
+
+            let a = read_slot(&mut flash, SLOT_A_OFFSET);
+            let b = read_slot(&mut flash, SLOT_B_OFFSET);
+
+            let next_sequence = a.map_or(0, |(seq, _)| seq).max(b.map_or(0, |(seq, _)| seq)).wrapping_add(1);
+
+            let (write_offset, erase_offset) = if a.is_none_or(|(seq_a, _)| b.is_some_and(|(seq_b, _)| seq_b >= seq_a)) {
+                (SLOT_A_OFFSET, SLOT_B_OFFSET)
+            } else {
+                (SLOT_B_OFFSET, SLOT_A_OFFSET)
+            };
+
+            write_slot(&mut flash, write_offset, next_sequence, config)?;
+            flash.blocking_erase(erase_offset, erase_offset + SECTOR_SIZE)
+        })
+    })
+}
+
+/// Erases both slots, reverting to [`Config::DEFAULT`] on the next [`read_config`].
+///
+/// Parks core1 for the duration; see [`write_config`]'s doc comment.
+pub fn erase_config(flash: &'static SharedFlash) -> Result<(), Error> {
+    with_core1_parked(|| {
+        flash.lock(|cell| {
+            let mut flash = cell.borrow_mut();
+            flash.blocking_erase(SLOT_A_OFFSET, SLOT_A_OFFSET + SECTOR_SIZE)?;
+            flash.blocking_erase(SLOT_B_OFFSET, SLOT_B_OFFSET + SECTOR_SIZE)
+        })
+    })
+}
+
+/// Requests core1 park itself in RAM (`core1::park_for_flash_access`) and spin-waits for its
+/// acknowledgement before running `f`, then releases it again once `f` returns.
+///
+/// Necessary around any flash erase/program: the RP2040's XIP cache can't serve flash reads to
+/// either core while one is mid-erase/program, and core1 is otherwise free-running out of flash
+/// (the embassy executor and its UART telemetry tasks).
+fn with_core1_parked<T>(f: impl FnOnce() -> T) -> T {
+    crate::CORE1_FLASH_LOCKOUT.store(true, Ordering::Release);
+    while !crate::CORE1_PARKED.load(Ordering::Acquire) {
+        core::hint::spin_loop();
+    }
+
+    let result = f();
+
+    crate::CORE1_FLASH_LOCKOUT.store(false, Ordering::Release);
+    result
+}
+
+fn read_slot(flash: &mut Flash<'_, FLASH, Blocking, FLASH_SIZE>, offset: u32) -> Option<(u32, Config)> {
+    let mut page = [0u8; PAGE_LEN];
+    flash.blocking_read(offset, &mut page).ok()?;
+
+    let sequence = u32::from_le_bytes(page[0..4].try_into().unwrap());
+    let record: [u8; RECORD_LEN] = page[4..4 + RECORD_LEN].try_into().unwrap();
+    let stored_crc = u32::from_le_bytes(page[4 + RECORD_LEN..8 + RECORD_LEN].try_into().unwrap());
+
+    if sequence == 0xFFFF_FFFF || crc32(&page[0..4 + RECORD_LEN]) != stored_crc {
+        return None;
+    }
+
+    Some((sequence, Config::from_bytes(&record)))
+}
+
+fn write_slot(
+    flash: &mut Flash<'_, FLASH, Blocking, FLASH_SIZE>,
+    offset: u32,
+    sequence: u32,
+    config: Config,
+) -> Result<(), Error> {
+    let mut page = [0u8; PAGE_LEN];
+    page[0..4].copy_from_slice(&sequence.to_le_bytes());
+    page[4..4 + RECORD_LEN].copy_from_slice(&config.to_bytes());
+
+    let crc = crc32(&page[0..4 + RECORD_LEN]);
+    page[4 + RECORD_LEN..8 + RECORD_LEN].copy_from_slice(&crc.to_le_bytes());
+
+    flash.blocking_write(offset, &page)
+}
+
+/// Reflected CRC32 (poly `0xEDB88320`), computed byte-at-a-time since pulling in a CRC crate
+/// isn't worth it for one page of config per boot.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
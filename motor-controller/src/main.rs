@@ -1,28 +1,51 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 #![no_main]
 
 mod config;
 mod core0;
 mod core1;
+mod flash_config;
+mod firmware_update;
+mod mixer;
 
-use core::cell::UnsafeCell;
+use core::cell::{RefCell, UnsafeCell};
 use core::ptr::addr_of_mut;
-use core::sync::atomic::{AtomicU8, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use defmt::info;
 use embassy_executor::Executor;
 use embassy_rp::clocks::ClockConfig;
 use embassy_rp::config::Config as EmbassyConfig;
+use embassy_rp::flash::{Blocking, Flash};
 use embassy_rp::i2c;
 use embassy_rp::i2c_slave::I2cSlave;
+#[cfg(feature = "spi-command-link")]
+use embassy_rp::gpio::{Input, Pull};
 use embassy_rp::multicore::{Stack, spawn_core1};
 use embassy_rp::peripherals::{I2C0, PIO0, PIO1};
 use embassy_rp::pio::{self, Pio};
 use embassy_rp::uart::{self, UartRx};
 use embassy_rp::bind_interrupts;
+use embassy_executor::Spawner;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_time::{Duration, Instant};
+use static_cell::StaticCell;
+
+#[cfg(not(feature = "bidirectional-dshot"))]
 use rp2040_dshot::StandardDShotTimings;
+#[cfg(not(feature = "bidirectional-dshot"))]
 use rp2040_dshot::driver::StandardDShotDriver;
+#[cfg(not(feature = "bidirectional-dshot"))]
 use rp2040_dshot::program::generate_standard_dshot_program;
-use static_cell::StaticCell;
+
+#[cfg(feature = "bidirectional-dshot")]
+use rp2040_dshot::BdDShotTimings;
+#[cfg(feature = "bidirectional-dshot")]
+use rp2040_dshot::driver::BdDShotDriver;
+#[cfg(feature = "bidirectional-dshot")]
+use rp2040_dshot::program::generate_bd_dshot_program;
+#[cfg(feature = "bidirectional-dshot")]
+use embassy_sync::channel::Channel;
 
 use panic_probe as _;
 use defmt_rtt as _;
@@ -30,13 +53,31 @@ use defmt_rtt as _;
 use crate::config::dshot::{DSHOT_SPEED, PIO_CLOCK_HZ, UPDATE_RATE_HZ};
 
 
+/// Width of the shared inter-core telemetry buffer, sized to the larger of the two frame
+/// layouts the `bidirectional-dshot` feature chooses between: the 10-byte KISS
+/// `TelemetryFrame` ([`core1::dshot_telemetry_task`]), or the 18-byte per-motor BDDShot RPM
+/// frame (8 x u16 RPM plus a validity bitmask byte plus a reserved byte, see
+/// [`core1::bd_dshot_telemetry_task`]). A layout narrower than this pads the rest with zeroes.
+pub const TELEMETRY_BUFFER_LEN: usize = 18;
+
 static mut CORE1_STACK: Stack<4096> = Stack::new();
 static CORE0_THREAD_EXECUTOR: StaticCell<Executor> = StaticCell::new();
 static CORE1_THREAD_EXECUTOR: StaticCell<Executor> = StaticCell::new();
-static TELEMETRY_BUFFERS: DoubleBuffer = DoubleBuffer {
-    buffers: UnsafeCell::new([[0u8; 10]; 2]),
-    current: AtomicU8::new(0)
+static ESC_CONFIG: StaticCell<flash_config::Config> = StaticCell::new();
+static FLASH_CELL: StaticCell<firmware_update::SharedFlash> = StaticCell::new();
+static TELEMETRY_BUFFERS: TelemetrySeqLock = TelemetrySeqLock {
+    payload: UnsafeCell::new([0u8; TELEMETRY_BUFFER_LEN]),
+    sequence: AtomicU32::new(0)
 };
+static TELEMETRY_LINK_STATS: TelemetryLinkStats = TelemetryLinkStats::new();
+
+/// Set by `flash_config::with_core1_parked` to ask core1 to stop executing flash-resident code
+/// for the duration of a flash erase/program (the RP2040's XIP cache can't serve either core
+/// while one is mid-erase/program). Cleared once the operation returns.
+pub static CORE1_FLASH_LOCKOUT: AtomicBool = AtomicBool::new(false);
+/// Set by `core1::park_for_flash_access` once core1 has reached its RAM-resident spin point in
+/// response to [`CORE1_FLASH_LOCKOUT`], so core0 knows it's safe to start the flash operation.
+pub static CORE1_PARKED: AtomicBool = AtomicBool::new(false);
 
 // Bind hardware interrupts
 bind_interrupts!(struct PioIrqs {
@@ -46,44 +87,167 @@ bind_interrupts!(struct PioIrqs {
 bind_i2c_interrupt!();
 bind_telemetry_interrupt!();
 
-
-/// Double buffered telemetry so writer never blocks reader (vroom vroom)
-struct DoubleBuffer {
-    buffers: UnsafeCell<[[u8; 10]; 2]>,
-    current: AtomicU8
+// One eRPM telemetry channel per PIO state machine, only used in bidirectional-DShot mode.
+#[cfg(feature = "bidirectional-dshot")]
+static ERPM_CHANNEL_PIO0_SM0: Channel<NoopRawMutex, u16, 3> = Channel::new();
+#[cfg(feature = "bidirectional-dshot")]
+static ERPM_CHANNEL_PIO0_SM1: Channel<NoopRawMutex, u16, 3> = Channel::new();
+#[cfg(feature = "bidirectional-dshot")]
+static ERPM_CHANNEL_PIO0_SM2: Channel<NoopRawMutex, u16, 3> = Channel::new();
+#[cfg(feature = "bidirectional-dshot")]
+static ERPM_CHANNEL_PIO0_SM3: Channel<NoopRawMutex, u16, 3> = Channel::new();
+#[cfg(feature = "bidirectional-dshot")]
+static ERPM_CHANNEL_PIO1_SM0: Channel<NoopRawMutex, u16, 3> = Channel::new();
+#[cfg(feature = "bidirectional-dshot")]
+static ERPM_CHANNEL_PIO1_SM1: Channel<NoopRawMutex, u16, 3> = Channel::new();
+#[cfg(feature = "bidirectional-dshot")]
+static ERPM_CHANNEL_PIO1_SM2: Channel<NoopRawMutex, u16, 3> = Channel::new();
+#[cfg(feature = "bidirectional-dshot")]
+static ERPM_CHANNEL_PIO1_SM3: Channel<NoopRawMutex, u16, 3> = Channel::new();
+
+
+/// Single-producer/single-consumer seqlock for handing the [`TELEMETRY_BUFFER_LEN`]-byte
+/// telemetry frame between cores without ever blocking the writer (vroom vroom).
+///
+/// The writer (core1) bumps `sequence` to odd, writes the payload, then bumps it to the next
+/// even value. The reader (core0) retries whenever it observes an odd sequence, or whenever
+/// the sequence changes mid-copy, which is the only way a torn read can happen here.
+struct TelemetrySeqLock {
+    payload: UnsafeCell<[u8; TELEMETRY_BUFFER_LEN]>,
+    sequence: AtomicU32
 }
 
 /// # Saftey
-/// Ensures that only one core writes to one buffer, while the other core reads from the other buffer. 
-/// [`AtomicU8`] and [`Ordering::Acquire`]/[`Ordering::Release`] provides nessasary synchronization.
-unsafe impl Sync for DoubleBuffer {}
-
-impl DoubleBuffer {
-    /// Reads data from buffer into provided output buffer
-    fn read(&self, output: &mut [u8; 10]) {
-        let current = self.current.load(Ordering::Acquire);
-        unsafe {
-            let buffers = *self.buffers.get();
-            let current_buf = buffers[current as usize];
-            output.copy_from_slice(&current_buf);
+/// `payload` is only ever written by the single writer core and read via the sequence-counter
+/// protocol below, which provides the nessasary synchronization across the two RP2040 cores.
+unsafe impl Sync for TelemetrySeqLock {}
+
+impl TelemetrySeqLock {
+    /// Reads the latest telemetry payload into the provided output buffer.
+    ///
+    /// Spins until it observes a consistent (non-torn) snapshot of the payload.
+    fn read(&self, output: &mut [u8; TELEMETRY_BUFFER_LEN]) {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before & 1 != 0 {
+                // Writer is mid-update; try again.
+                continue;
+            }
+
+            unsafe {
+                output.copy_from_slice(&*self.payload.get());
+            }
+
+            let after = self.sequence.load(Ordering::Acquire);
+            if after == before {
+                return;
+            }
         }
     }
 
-    /// Writes data from provieded input buffer into the correct internal buffer.
-    fn write(&self, data: &mut [u8; 10]) {
-        let current = self.current.load(Ordering::Acquire);
-        
+    /// Writes `data` into the payload. Never blocks; the reader is responsible for retrying
+    /// if it observes a torn snapshot.
+    fn write(&self, data: &mut [u8; TELEMETRY_BUFFER_LEN]) {
+        let sequence = self.sequence.load(Ordering::Relaxed);
+
+        self.sequence.store(sequence.wrapping_add(1), Ordering::Release);
         unsafe {
-            let buffers = *self.buffers.get();
-            let mut current_buf = buffers[current as usize];
-            current_buf.copy_from_slice(data);
+            (*self.payload.get()).copy_from_slice(data);
         }
+        self.sequence.store(sequence.wrapping_add(2), Ordering::Release);
+    }
+}
+
 
-        // Switch buffer
-        self.current.store(1 - current, Ordering::Release);
+/// Link-health counters for the KISS telemetry UART, updated by core1's telemetry loop and
+/// read out over the core0 I2C register map so the host can see a degrading link before it
+/// fails outright.
+struct TelemetryLinkStats {
+    overruns: AtomicU32,
+    framing_errors: AtomicU32,
+    parity_errors: AtomicU32,
+    breaks: AtomicU32,
+    /// Frames that arrived complete (10 bytes) but failed CRC.
+    crc_failures: AtomicU32,
+    /// Frames that were cut short by the idle gap or a UART error before 10 bytes arrived;
+    /// indicates a wiring fault rather than line noise.
+    short_reads: AtomicU32,
+    successful_frames: AtomicU32,
+    frames_since_last_good: AtomicU32,
+}
+
+impl TelemetryLinkStats {
+    const fn new() -> Self {
+        Self {
+            overruns: AtomicU32::new(0),
+            framing_errors: AtomicU32::new(0),
+            parity_errors: AtomicU32::new(0),
+            breaks: AtomicU32::new(0),
+            crc_failures: AtomicU32::new(0),
+            short_reads: AtomicU32::new(0),
+            successful_frames: AtomicU32::new(0),
+            frames_since_last_good: AtomicU32::new(0),
+        }
+    }
+
+    fn record_good_frame(&self) {
+        self.successful_frames.fetch_add(1, Ordering::Relaxed);
+        self.frames_since_last_good.store(0, Ordering::Relaxed);
+    }
+
+    fn record_bad_frame(&self) {
+        self.frames_since_last_good.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Builds the per-motor DShot drivers from the enabled state machines.
+///
+/// In bidirectional-DShot mode each driver also spawns a background task reading eRPM
+/// telemetry back on the signal wire, hence the [`Spawner`] parameter.
+#[cfg(feature = "bidirectional-dshot")]
+fn build_sm_drivers(pio0: Pio<'static, PIO0>, pio1: Pio<'static, PIO1>, spawner: &Spawner) -> crate::core0::SmDriverBatch {
+    crate::core0::SmDriverBatch {
+        pio0_sm0: BdDShotDriver::new(pio0.sm0, pio0.irq0, &ERPM_CHANNEL_PIO0_SM0, spawner).expect("Failed to init BdDShot driver for PIO0 SM0!"),
+        pio0_sm1: BdDShotDriver::new(pio0.sm1, pio0.irq1, &ERPM_CHANNEL_PIO0_SM1, spawner).expect("Failed to init BdDShot driver for PIO0 SM1!"),
+        pio0_sm2: BdDShotDriver::new(pio0.sm2, pio0.irq2, &ERPM_CHANNEL_PIO0_SM2, spawner).expect("Failed to init BdDShot driver for PIO0 SM2!"),
+        pio0_sm3: BdDShotDriver::new(pio0.sm3, pio0.irq3, &ERPM_CHANNEL_PIO0_SM3, spawner).expect("Failed to init BdDShot driver for PIO0 SM3!"),
+        pio1_sm0: BdDShotDriver::new(pio1.sm0, pio1.irq0, &ERPM_CHANNEL_PIO1_SM0, spawner).expect("Failed to init BdDShot driver for PIO1 SM0!"),
+        pio1_sm1: BdDShotDriver::new(pio1.sm1, pio1.irq1, &ERPM_CHANNEL_PIO1_SM1, spawner).expect("Failed to init BdDShot driver for PIO1 SM1!"),
+        pio1_sm2: BdDShotDriver::new(pio1.sm2, pio1.irq2, &ERPM_CHANNEL_PIO1_SM2, spawner).expect("Failed to init BdDShot driver for PIO1 SM2!"),
+        pio1_sm3: BdDShotDriver::new(pio1.sm3, pio1.irq3, &ERPM_CHANNEL_PIO1_SM3, spawner).expect("Failed to init BdDShot driver for PIO1 SM3!"),
+    }
+}
+
+#[cfg(not(feature = "bidirectional-dshot"))]
+fn build_sm_drivers<'d>(pio0: Pio<'d, PIO0>, pio1: Pio<'d, PIO1>, _spawner: &Spawner) -> crate::core0::SmDriverBatch {
+    crate::core0::SmDriverBatch {
+        pio0_sm0: StandardDShotDriver::new(pio0.sm0),
+        pio0_sm1: StandardDShotDriver::new(pio0.sm1),
+        pio0_sm2: StandardDShotDriver::new(pio0.sm2),
+        pio0_sm3: StandardDShotDriver::new(pio0.sm3),
+        pio1_sm0: StandardDShotDriver::new(pio1.sm0),
+        pio1_sm1: StandardDShotDriver::new(pio1.sm1),
+        pio1_sm2: StandardDShotDriver::new(pio1.sm2),
+        pio1_sm3: StandardDShotDriver::new(pio1.sm3),
     }
 }
 
+/// Waits briefly for the telemetry link to decode at least one good ESC frame. By the time
+/// this runs, `build_sm_drivers` has already returned (or panicked), so a successful frame is
+/// the last piece of evidence that a freshly flashed image is actually working end to end;
+/// [`firmware_update::confirm_boot`] only marks the image booted if this passes.
+fn run_boot_self_test() -> bool {
+    let deadline = Instant::now() + Duration::from_millis(500);
+
+    loop {
+        if TELEMETRY_LINK_STATS.successful_frames.load(Ordering::Relaxed) > 0 {
+            return true;
+        }
+        if Instant::now() > deadline {
+            return false;
+        }
+    }
+}
 
 fn enable_sms<'d>(pio0: &mut Pio<'d, PIO0>, pio1: &mut Pio<'d, PIO1>) {
     pio0.sm0.set_enable(true);
@@ -106,11 +270,17 @@ fn main() -> ! {
     let p = embassy_rp::init(embassy_config);
     info!("Fetched RP2040 peripherals!");
 
+    #[cfg(not(feature = "bidirectional-dshot"))]
     let timings = StandardDShotTimings::new(DSHOT_SPEED, PIO_CLOCK_HZ, UPDATE_RATE_HZ).expect("Failed to get DShot timings!");
+    #[cfg(feature = "bidirectional-dshot")]
+    let timings = BdDShotTimings::new(DSHOT_SPEED, PIO_CLOCK_HZ, UPDATE_RATE_HZ);
     info!("Got DShot Timings!");
 
-    info!("Clock divider: {}", crate::config::dshot::PIO_CLOCK_DIVDER.to_num::<f32>());
+    info!("Clock divider: {}", crate::config::dshot::PIO_CLOCK_DIVIDER.to_num::<f32>());
+    #[cfg(not(feature = "bidirectional-dshot"))]
     let program = generate_standard_dshot_program(&timings);
+    #[cfg(feature = "bidirectional-dshot")]
+    let program = generate_bd_dshot_program(&timings);
     info!("Generated DShot Program!");
 
     let mut pio0 = Pio::new(p.PIO0, PioIrqs);
@@ -121,65 +291,52 @@ fn main() -> ! {
     pio1.common.load_program(&program);
     info!("Loaded PIO programs!");
 
-    let (
-        top_front_right_pin,
-        top_front_left_pin,
-        top_back_right_pin,
-        top_back_left_pin,
-        bottom_front_right_pin,
-        bottom_front_left_pin,
-        bottom_back_right_pin,
-        bottom_back_left_pin,
-    ) = get_dshot_pins!(p);
-
+    // The board's wiring harness is chosen right here: each argument only typechecks if it's
+    // a pin `config::dshot` actually enumerates as legal for that leg's PIO.
     config::dshot::set_pio_config(
         &mut pio0,
         &mut pio1,
-        top_front_right_pin,
-        top_front_left_pin,
-        top_back_right_pin,
-        top_back_left_pin,
-        bottom_front_right_pin,
-        bottom_front_left_pin,
-        bottom_back_right_pin,
-        bottom_back_left_pin,
+        p.PIN_13,
+        p.PIN_14,
+        p.PIN_15,
+        p.PIN_16,
+        p.PIN_17,
+        p.PIN_18,
+        p.PIN_19,
+        p.PIN_20,
     );
     info!("Setup SM Configs!");
 
     enable_sms(&mut pio0, &mut pio1);
-    let sm_drivers = crate::core0::SmDriverBatch {
-        pio0_sm0: StandardDShotDriver::new(pio0.sm0),
-        pio0_sm1: StandardDShotDriver::new(pio0.sm1),
-        pio0_sm2: StandardDShotDriver::new(pio0.sm2),
-        pio0_sm3: StandardDShotDriver::new(pio0.sm3),
-        pio1_sm0: StandardDShotDriver::new(pio1.sm0),
-        pio1_sm1: StandardDShotDriver::new(pio1.sm1),
-        pio1_sm2: StandardDShotDriver::new(pio1.sm2),
-        pio1_sm3: StandardDShotDriver::new(pio1.sm3),
-    };
     info!("Enabled SMs!");
 
+    // Same idea as the DShot pins above: the board's telemetry wiring is chosen right here,
+    // and only typechecks for a pin `config::telemetry` enumerates as legal for that UART role.
     #[cfg(not(feature = "dummy-telemetry"))]
     let uart_rx = {
-        use uart::Blocking;
-        let (uart_peri, telemetry_pin, dma_channel) = get_telemetry_peripherals!(p);
+        use uart::Async;
         let uart_config = config::telemetry::get_uart_config();
-        UartRx::<Blocking>::new(uart_peri, telemetry_pin, UartIrq, dma_channel, uart_config)
+        match config::telemetry::RxPin::from(p.PIN_5) {
+            config::telemetry::RxPin::Pin5(pin) => UartRx::<Async>::new(p.UART1, pin, UartIrq, p.DMA_CH0, uart_config),
+            config::telemetry::RxPin::Pin9(pin) => UartRx::<Async>::new(p.UART1, pin, UartIrq, p.DMA_CH0, uart_config),
+        }
     };
 
     #[cfg(feature = "dummy-telemetry")]
     let (uart_rx, uart_tx) = {
         use uart::{UartTx, Async};
-        let (
-            uart_peri_rx, telemetry_pin_rx, dma_channel_rx,
-            uart_peri_tx, telemetry_pin_tx, dma_channel_tx
-        ) = get_telemetry_peripherals!(p);
         let uart_config = config::telemetry::get_uart_config();
 
-        (
-            UartRx::<Async>::new(uart_peri_rx, telemetry_pin_rx, UartIrq, dma_channel_rx, uart_config),
-            UartTx::<Async>::new(uart_peri_tx, telemetry_pin_tx, dma_channel_tx, uart_config),
-        )
+        let uart_rx = match config::telemetry::RxPin::from(p.PIN_5) {
+            config::telemetry::RxPin::Pin5(pin) => UartRx::<Async>::new(p.UART1, pin, UartIrq, p.DMA_CH0, uart_config),
+            config::telemetry::RxPin::Pin9(pin) => UartRx::<Async>::new(p.UART1, pin, UartIrq, p.DMA_CH0, uart_config),
+        };
+        let uart_tx = match config::telemetry::TxPin::from(p.PIN_12) {
+            config::telemetry::TxPin::Pin12(pin) => UartTx::<Async>::new(p.UART0, pin, p.DMA_CH1, uart_config),
+            config::telemetry::TxPin::Pin28(pin) => UartTx::<Async>::new(p.UART0, pin, p.DMA_CH1, uart_config),
+        };
+
+        (uart_rx, uart_tx)
     };
     info!("Setup UART Peripheral!");
 
@@ -198,21 +355,86 @@ fn main() -> ! {
                 spawner
                     .spawn(crate::core1::dummy_telemetry_writer(uart_tx))
                     .expect("Failed to spawn DShot dummy telmetry writer task!");
+
+                #[cfg(feature = "bidirectional-dshot")]
+                spawner
+                    .spawn(crate::core1::bd_dshot_telemetry_task([
+                        &ERPM_CHANNEL_PIO0_SM0,
+                        &ERPM_CHANNEL_PIO0_SM1,
+                        &ERPM_CHANNEL_PIO0_SM2,
+                        &ERPM_CHANNEL_PIO0_SM3,
+                        &ERPM_CHANNEL_PIO1_SM0,
+                        &ERPM_CHANNEL_PIO1_SM1,
+                        &ERPM_CHANNEL_PIO1_SM2,
+                        &ERPM_CHANNEL_PIO1_SM3,
+                    ]))
+                    .expect("Failed to spawn BDDShot telemetry aggregation task!");
             })
         },
     );
 
 
-    let i2c_config = config::i2c::new();
-    let (i2c_peri, scl, sda) = get_i2c_peripherals!(p);
-    let i2c_device = I2cSlave::new(i2c_peri, scl, sda, I2cIrq, i2c_config);
+    // `spi-command-link` and the default (I2C) each claim exclusive ownership of the eight PIO
+    // state machines via `sm_drivers` below, so only one command-link peripheral is ever set up.
+    #[cfg(not(feature = "spi-command-link"))]
+    let i2c_device = {
+        let i2c_config = config::i2c::new();
+        let (i2c_peri, scl, sda) = get_i2c_peripherals!(p);
+        let i2c_device = I2cSlave::new(i2c_peri, scl, sda, I2cIrq, i2c_config);
+        info!("Setup I2C peripheral!");
+        i2c_device
+    };
+
+    let mut flash = Flash::<_, Blocking, { flash_config::FLASH_SIZE }>::new_blocking(p.FLASH);
+    let esc_config = ESC_CONFIG.init(flash_config::read_config(&mut flash));
+    info!("Loaded ESC config, active state machines: {}", esc_config.active_sm_count);
 
-    info!("Setup I2C peripheral!");
+    // Promote flash to 'static, shared, so a later DFU session (see `firmware_update`) can
+    // reach it from core0's SPI task without the RP2040 needing a second `FLASH` peripheral.
+    let flash = FLASH_CELL.init(Mutex::new(RefCell::new(flash)));
 
     let core0_thread_executor = CORE0_THREAD_EXECUTOR.init(Executor::new());
     core0_thread_executor.run(|spawner| {
+        let sm_drivers = build_sm_drivers(pio0, pio1, &spawner);
+        info!("Initialized all eight DShot state machines!");
+
+        let self_test_passed = run_boot_self_test();
+        firmware_update::confirm_boot(flash, self_test_passed);
+
+        #[cfg(feature = "spi-self-test")]
+        {
+            // Bench jumper: LoopbackPeripheral's own MOSI (PIN_11) looped back to its own
+            // MISO (PIN_8) so the transfer below verifies the DMA/pin setup stands alone.
+            let loopback_spi = config::spi::new_master(
+                p.SPI1,
+                p.PIN_10,
+                p.PIN_11,
+                p.PIN_8,
+                p.DMA_CH4,
+                p.DMA_CH5,
+                config::spi::loopback_config(),
+            );
+            spawner
+                .spawn(core0::spi_self_test_task(loopback_spi))
+                .expect("Failed to spawn SPI loopback self-test task!");
+        }
+
+        // Exactly one of `spi-command-link` or the default (I2C) may be built at a time:
+        // `core0::i2c_task` and `core0::spi_task` each need exclusive ownership of `sm_drivers`,
+        // since there's only the one set of eight PIO state machines to drive.
+        #[cfg(feature = "spi-command-link")]
+        {
+            // GPIO number matches `config::spi::CS_PIN_NUM`.
+            let cs_pin = Input::new(p.PIN_21, Pull::None);
+
+            spawner
+                .spawn(core0::spi_task(cs_pin, sm_drivers, esc_config, flash))
+                .expect("Failed to spawn spi task!");
+        }
+
+        #[cfg(not(feature = "spi-command-link"))]
         spawner
-            .spawn(core0::i2c_task(i2c_device, sm_drivers))
+            .spawn(core0::i2c_task(i2c_device, sm_drivers, esc_config, flash))
             .expect("Failed to spawn i2c task!");
-    }) 
+    })
 }
\ No newline at end of file
@@ -0,0 +1,202 @@
+//! 6-DOF thrust-allocation mixer.
+//!
+//! `config::dshot` names its eight state machines by the vectored-thruster corner each drives
+//! (top/bottom x front/back x left/right), but nothing upstream of `core0::SmDriverBatch` turns
+//! a vehicle-level command into the eight per-motor throttles that layout implies. This module
+//! is that translation: a [`CommandVector`] times an [`AllocationMatrix`] gives eight signed,
+//! saturated throttle fractions, which [`signed_to_3d_throttle`] then maps onto the
+//! bidirectional (3D-mode) DShot throttle range for [`core0::SmDriverBatch`].
+
+/// Number of motors a [`CommandVector`] is allocated across.
+pub const MOTOR_COUNT: usize = 8;
+
+/// Number of degrees of freedom in a [`CommandVector`].
+pub const AXIS_COUNT: usize = 6;
+
+/// A vehicle-level 6-DOF command, each axis in `[-1.0, 1.0]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CommandVector {
+    pub surge: f32,
+    pub sway: f32,
+    pub heave: f32,
+    pub roll: f32,
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+impl CommandVector {
+    pub const ZERO: Self = Self { surge: 0.0, sway: 0.0, heave: 0.0, roll: 0.0, pitch: 0.0, yaw: 0.0 };
+
+    const fn as_array(self) -> [f32; AXIS_COUNT] {
+        [self.surge, self.sway, self.heave, self.roll, self.pitch, self.yaw]
+    }
+
+    /// Sets the axis at `index` (same order as [`Self::as_array`]: surge, sway, heave, roll,
+    /// pitch, yaw), for a host command link that addresses one axis per write.
+    ///
+    /// Out-of-range indices are a no-op, since the command link already validates the register
+    /// address before computing `index`.
+    pub fn set_axis(&mut self, index: usize, value: f32) {
+        match index {
+            0 => self.surge = value,
+            1 => self.sway = value,
+            2 => self.heave = value,
+            3 => self.roll = value,
+            4 => self.pitch = value,
+            5 => self.yaw = value,
+            _ => {}
+        }
+    }
+}
+
+/// One row per motor (in `core0::SmDriverBatch` field order, i.e. PIO0 SM0-3 then PIO1 SM0-3),
+/// one column per [`CommandVector`] axis (surge, sway, heave, roll, pitch, yaw).
+pub type AllocationMatrix = [[f32; AXIS_COUNT]; MOTOR_COUNT];
+
+/// Default allocation for the geometry `config::dshot`'s pin names describe: eight thrusters,
+/// one at each corner of a box, each canted inward so it has a component along all three
+/// translation axes and, by virtue of its position, all three rotation axes too.
+///
+/// Row order matches `core0::SmDriverBatch`: top_front_right, top_front_left, top_back_right,
+/// top_back_left, bottom_front_right, bottom_front_left, bottom_back_right, bottom_back_left.
+/// A board with a different thruster geometry should build its own matrix rather than edit
+/// this one; [`mix`] takes it as a parameter for exactly that reason.
+pub const DEFAULT_ALLOCATION_MATRIX: AllocationMatrix = {
+    const V: f32 = 1.0; // top (+) / bottom (-)
+    const F: f32 = 1.0; // front (+) / back (-)
+    const L: f32 = 1.0; // right (+) / left (-)
+
+    // surge, sway, heave, roll, pitch, yaw
+    [
+        [F, L, V, V * L, V * F, F * L],     // top_front_right
+        [F, -L, V, V * -L, V * F, F * -L],  // top_front_left
+        [-F, L, V, V * L, V * -F, -F * L],  // top_back_right
+        [-F, -L, V, V * -L, V * -F, -F * -L], // top_back_left
+        [F, L, -V, -V * L, -V * F, F * L],  // bottom_front_right
+        [F, -L, -V, -V * -L, -V * F, F * -L], // bottom_front_left
+        [-F, L, -V, -V * L, -V * -F, -F * L], // bottom_back_right
+        [-F, -L, -V, -V * -L, -V * -F, -F * -L], // bottom_back_left
+    ]
+};
+
+/// Multiplies `command` by `matrix` to get eight signed per-motor throttle fractions, then
+/// scales the whole result down (preserving every motor's relative share, and so the commanded
+/// torque direction) if any motor would otherwise exceed full scale.
+#[must_use]
+pub fn mix(command: CommandVector, matrix: &AllocationMatrix) -> [f32; MOTOR_COUNT] {
+    let axes = command.as_array();
+    let mut out = [0.0f32; MOTOR_COUNT];
+
+    for (motor, row) in out.iter_mut().zip(matrix.iter()) {
+        *motor = row.iter().zip(axes.iter()).map(|(coeff, axis)| coeff * axis).sum();
+    }
+
+    let max_abs = out.iter().fold(0.0f32, |max, value| max.max(value.abs()));
+
+    if max_abs > 1.0 {
+        for motor in &mut out {
+            *motor /= max_abs;
+        }
+    }
+
+    out
+}
+
+/// Maps a signed, saturated throttle fraction (`[-1.0, 1.0]`) onto the bidirectional (3D-mode)
+/// DShot throttle range: `0..=999` is full reverse to stopped, `1000..=1999` is stopped to full
+/// forward. Matches the split `rp2040_dshot::encoder::Frame::from_throttle` expects once the
+/// ESC has been sent `Command::ThreeDModeOn`.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn signed_to_3d_throttle(value: f32) -> u16 {
+    let value = value.clamp(-1.0, 1.0);
+    let magnitude = (value.abs() * 999.0).round() as u16;
+
+    if value >= 0.0 {
+        1000 + magnitude
+    } else {
+        999 - magnitude
+    }
+}
+
+/// Runs [`mix`] then [`signed_to_3d_throttle`] on every motor, producing the eight throttle
+/// values `core0`'s per-state-machine DShot path expects.
+#[must_use]
+pub fn mix_to_throttles(command: CommandVector, matrix: &AllocationMatrix) -> [u16; MOTOR_COUNT] {
+    mix(command, matrix).map(signed_to_3d_throttle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_command_mixes_to_all_zero() {
+        let out = mix(CommandVector::ZERO, &DEFAULT_ALLOCATION_MATRIX);
+        assert_eq!(out, [0.0; MOTOR_COUNT]);
+    }
+
+    #[test]
+    fn mix_scales_down_when_any_motor_would_exceed_full_scale() {
+        // Pure heave at full scale drives every row to +/-1.0 already; stacking surge on top
+        // should scale the whole vector down rather than let any motor clip past 1.0.
+        let command = CommandVector { surge: 1.0, heave: 1.0, ..CommandVector::ZERO };
+        let out = mix(command, &DEFAULT_ALLOCATION_MATRIX);
+
+        let max_abs = out.iter().fold(0.0f32, |max, value| max.max(value.abs()));
+        assert!(max_abs <= 1.0 + f32::EPSILON);
+
+        // Scaling is uniform, so relative proportions between motors are preserved.
+        let unscaled = {
+            let axes = command.as_array();
+            let mut raw = [0.0f32; MOTOR_COUNT];
+            for (motor, row) in raw.iter_mut().zip(DEFAULT_ALLOCATION_MATRIX.iter()) {
+                *motor = row.iter().zip(axes.iter()).map(|(coeff, axis)| coeff * axis).sum();
+            }
+            raw
+        };
+        let scale = out[0] / unscaled[0];
+        for (scaled, raw) in out.iter().zip(unscaled.iter()) {
+            assert!((scaled - raw * scale).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn signed_to_3d_throttle_maps_stopped_and_extremes() {
+        assert_eq!(signed_to_3d_throttle(0.0), 1000);
+        assert_eq!(signed_to_3d_throttle(1.0), 1999);
+        assert_eq!(signed_to_3d_throttle(-1.0), 0);
+    }
+
+    #[test]
+    fn signed_to_3d_throttle_clamps_out_of_range_input() {
+        assert_eq!(signed_to_3d_throttle(2.0), 1999);
+        assert_eq!(signed_to_3d_throttle(-2.0), 0);
+    }
+
+    #[test]
+    fn mix_to_throttles_maps_every_motor_into_3d_range() {
+        let command = CommandVector { yaw: 1.0, ..CommandVector::ZERO };
+        let throttles = mix_to_throttles(command, &DEFAULT_ALLOCATION_MATRIX);
+
+        for throttle in throttles {
+            assert!(throttle <= 1999);
+        }
+    }
+
+    #[test]
+    fn set_axis_writes_each_axis_in_order() {
+        let mut command = CommandVector::ZERO;
+        for (index, value) in [1.0, 2.0, 3.0, 4.0, 5.0, 6.0].into_iter().enumerate() {
+            command.set_axis(index, value);
+        }
+        assert_eq!(command, CommandVector { surge: 1.0, sway: 2.0, heave: 3.0, roll: 4.0, pitch: 5.0, yaw: 6.0 });
+    }
+
+    #[test]
+    fn set_axis_ignores_out_of_range_index() {
+        let mut command = CommandVector::ZERO;
+        command.set_axis(AXIS_COUNT, 1.0);
+        assert_eq!(command, CommandVector::ZERO);
+    }
+}
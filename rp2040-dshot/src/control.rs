@@ -0,0 +1,91 @@
+//! Closed-loop RPM control.
+//!
+//! Combines DShot's throttle encoding ([`crate::encoder::Frame::from_throttle`]) with measured
+//! speed from ERPM telemetry ([`crate::encoder::StandardERpmFrame::compute_rpm`],
+//! [`crate::encoder::ExtendedERpmFrame::compute_rpm`], or a KISS
+//! [`crate::encoder::TelemetryFrame`]'s `e_rpm`, scaled to mechanical RPM by the caller) into a
+//! discrete PID loop that outputs a throttle ready to hand straight back to `from_throttle`.
+
+/// Tunable parameters for [`RpmController`]. All gains operate on RPM error/rate and produce
+/// throttle units (DShot's 0-1999 range).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gains {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    /// Clamps the accumulated integral of RPM error over time (RPM-seconds, before `ki` is
+    /// applied) so the motor sitting saturated
+    /// or stalled for a while doesn't leave a huge integral to unwind once it's freed.
+    pub integral_clamp: f32,
+    /// Maximum throttle change [`RpmController::update`] allows per call, in throttle units.
+    pub max_slew_rate: f32,
+}
+
+/// Discrete PID controller driving a target mechanical RPM.
+///
+/// Differentiates on the measurement rather than the error, so a setpoint change doesn't cause
+/// a derivative spike ("derivative kick"); the slew-rate limit bounds how fast the commanded
+/// throttle itself can move, independent of how aggressive the gains are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RpmController {
+    gains: Gains,
+    integral: f32,
+    last_measured_rpm: Option<f32>,
+    last_throttle: u16,
+}
+
+impl RpmController {
+    #[must_use]
+    pub const fn new(gains: Gains) -> Self {
+        Self {
+            gains,
+            integral: 0.0,
+            last_measured_rpm: None,
+            last_throttle: 0,
+        }
+    }
+
+    /// Runs one discrete PID update and returns the next throttle to send via
+    /// `Frame::from_throttle`.
+    ///
+    /// `dt` is the time since the previous call, in seconds.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn update(&mut self, setpoint_rpm: f32, measured_rpm: f32, dt: f32) -> u16 {
+        let error = setpoint_rpm - measured_rpm;
+
+        self.integral = (self.integral + error * dt)
+            .clamp(-self.gains.integral_clamp, self.gains.integral_clamp);
+
+        let measurement_rate = self
+            .last_measured_rpm
+            .map_or(0.0, |last_measured_rpm| (measured_rpm - last_measured_rpm) / dt);
+        self.last_measured_rpm = Some(measured_rpm);
+
+        let output = self.gains.kp * error + self.gains.ki * self.integral - self.gains.kd * measurement_rate;
+        let desired_throttle = output.clamp(0.0, 1999.0);
+
+        let last_throttle = f32::from(self.last_throttle);
+        let delta = (desired_throttle - last_throttle).clamp(-self.gains.max_slew_rate, self.gains.max_slew_rate);
+        let next_throttle = (last_throttle + delta).clamp(0.0, 1999.0);
+
+        self.last_throttle = next_throttle.round() as u16;
+        self.last_throttle
+    }
+
+    /// The last throttle this controller commanded, via either [`Self::update`] or
+    /// [`Self::hold_last`].
+    #[must_use]
+    pub const fn last_throttle(&self) -> u16 {
+        self.last_throttle
+    }
+
+    /// Call instead of [`Self::update`] when a telemetry frame fails CRC: holds the last
+    /// commanded throttle steady, so a single dropped packet doesn't spike the motor, and drops
+    /// the derivative term's reference measurement so the next good sample doesn't compute its
+    /// rate across the gap.
+    pub fn hold_last(&mut self) -> u16 {
+        self.last_measured_rpm = None;
+        self.last_throttle
+    }
+}
@@ -134,7 +134,11 @@ impl<PIO: Instance, const SM: usize>
     PrivateDShotDriver<'static, PIO, SM>
     for BdDShotDriver<PIO, SM>
 {
-    type Variant = StandardDShotVariant;
+    // BDShot ESCs always reply with telemetry, so the telemetry-request bit isn't optional the
+    // way it is on a standard (uni-directional) link; the wire protocol instead always sends the
+    // inverted CRC nibble so the ESC can tell it's talking to a bidirectional-capable flight
+    // controller. See `generate_bd_dshot_program`'s PIO turnaround for the reply itself.
+    type Variant = InvertedDShotVariant;
 
     fn tx(&mut self) -> &mut StateMachineTx<'static, PIO, SM> {
         self.tx_ref
@@ -246,51 +250,6 @@ generate_erpm_reader!(PIO1, 1);
 generate_erpm_reader!(PIO1, 2);
 generate_erpm_reader!(PIO1, 3);
 
-const GCR_DECODING_MAP: [Option<u8>; 32] = [
-    None,
-    None,
-    None,
-    None,
-    None,
-    None,
-    None,
-    None,
-    None,
-    Some(0b_1001), // 0b_01001 -> 0b_1001
-    Some(0b_1010), // 0b_01010 -> 0b_1010
-    Some(0b_1011), // 0b_01011 -> 0b_1011
-    None,
-    Some(0b_1101), // 0b_01101 -> 0b_1101
-    Some(0b_1110), // 0b_01110 -> 0b_1110
-    Some(0b_1111), // 0b_01111 -> 0b_1111
-    None,
-    None,
-    Some(0b_0010), // 0b_10010 -> 0b_0010
-    Some(0b_0011), // 0b_10011 -> 0b_0011
-    None,
-    Some(0b_0101), // 0b_10101 -> 0b_0101
-    Some(0b_0110), // 0b_10110 -> 0b_0110
-    Some(0b_0111), // 0b_10111 -> 0b_0111
-    None,
-    Some(0b_0000), // 0b_11001 -> 0b_0000
-    Some(0b_1000), // 0b_11010 -> 0b_1000
-    Some(0b_0001), // 0b_11011 -> 0b_0001
-    None,
-    Some(0b_0100), // 0b_11101 -> 0b_0100
-    Some(0b_1100), // 0b_11110 -> 0b_1100
-    None,
-];
-
-fn decode_gcr(gcr: u32) -> Option<u16> {
-    let mut result: u16 = 0;
-    for shift in 1..=4 {
-        let index = ((gcr >> (shift * 5)) & 0x1F) as usize;
-        let nibble = GCR_DECODING_MAP[index]?;
-        result |= (nibble as u16) << (shift * 4)
-    }
-    Some(result)
-}
-
 async fn erpm_reader_task_impl<'d, PIO: Instance, const SM: usize>(
     mut irq: Irq<'static, PIO, SM>,
     rx_ref: &'static mut StateMachineRx<'d, PIO, SM>,
@@ -311,9 +270,7 @@ async fn erpm_reader_task_impl<'d, PIO: Instance, const SM: usize>(
             continue;
         };
 
-        let gcr = value ^ (value >> 1);
-
-        let Some(data) = decode_gcr(gcr) else {
+        let Ok(data) = crate::gcr::decode_raw_bits(value) else {
             defmt::error!("Failed to read erpm data from PIO {}: gcr decode failed", SM);
             continue;
         };
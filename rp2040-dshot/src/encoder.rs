@@ -1,4 +1,7 @@
-use core::{marker::PhantomData, num::NonZeroU32};
+use core::{
+    marker::PhantomData,
+    num::{NonZeroU32, NonZeroU8},
+};
 use num_enum::TryFromPrimitive;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -191,6 +194,102 @@ impl<P: DShotVariant> Frame<P> {
     pub const fn inner(&self) -> u16 {
         self.inner
     }
+
+    /// Renders this frame into one duty-cycle fraction per symbol (`1.0` meaning always high),
+    /// most significant bit first, for a timer/DMA peripheral that reloads its PWM compare value
+    /// once per bit period straight out of a DMA buffer. The trailing [`OUTPUT_SYMBOL_COUNT`]th
+    /// symbol is the mandatory inter-frame idle.
+    ///
+    /// `P::IS_INVERTED` flips every fraction (`duty -> 1.0 - duty`) so the inverted variant used
+    /// for bidirectional DShot clocks out at the correct polarity.
+    #[must_use]
+    pub fn to_duty_samples(&self) -> [f32; OUTPUT_SYMBOL_COUNT] {
+        let mut samples = [IDLE_DUTY; OUTPUT_SYMBOL_COUNT];
+
+        for (bit_idx, sample) in samples.iter_mut().take(FRAME_BIT_COUNT).enumerate() {
+            *sample = bit_duty(self.bit(bit_idx));
+        }
+
+        if P::IS_INVERTED {
+            for sample in &mut samples {
+                *sample = 1.0 - *sample;
+            }
+        }
+
+        samples
+    }
+
+    /// Renders this frame into `(high_ticks, low_ticks)` pairs, most significant bit first, for
+    /// a PWM/RMT-style peripheral that clocks out an explicit tick count per symbol instead of a
+    /// duty-cycle sample. The trailing pair is the mandatory inter-frame idle.
+    ///
+    /// `timer_clock_hz` is the tick rate the caller's peripheral counts at. Returns [`None`] if
+    /// that clock is too coarse to represent one `speed` bit period as at least one whole tick.
+    /// `P::IS_INVERTED` swaps each pair's halves so the inverted variant used for bidirectional
+    /// DShot clocks out at the correct polarity.
+    #[must_use]
+    pub fn to_pulse_ticks(&self, speed: DShotSpeed, timer_clock_hz: u32) -> Option<[(u32, u32); OUTPUT_SYMBOL_COUNT]> {
+        let ticks_per_bit = bit_period_ticks(speed, timer_clock_hz)?;
+
+        let mut pulses = [(0u32, 0u32); OUTPUT_SYMBOL_COUNT];
+
+        for (bit_idx, pulse) in pulses.iter_mut().take(FRAME_BIT_COUNT).enumerate() {
+            *pulse = split_ticks(ticks_per_bit, bit_duty(self.bit(bit_idx)), P::IS_INVERTED);
+        }
+
+        pulses[FRAME_BIT_COUNT] = split_ticks(ticks_per_bit, IDLE_DUTY, P::IS_INVERTED);
+
+        Some(pulses)
+    }
+
+    /// Value of data bit `idx` (0 = most significant).
+    #[must_use]
+    const fn bit(&self, idx: usize) -> bool {
+        (self.inner >> (FRAME_BIT_COUNT - 1 - idx)) & 1 != 0
+    }
+}
+
+/// Number of data-bit symbols in one DShot frame.
+const FRAME_BIT_COUNT: usize = 16;
+
+/// Number of symbols [`Frame::to_duty_samples`]/[`Frame::to_pulse_ticks`] emit: one per data bit
+/// plus one trailing symbol for the mandatory inter-frame idle.
+pub const OUTPUT_SYMBOL_COUNT: usize = FRAME_BIT_COUNT + 1;
+
+/// Duty-cycle fraction (high time / bit period) of a `1` bit, before accounting for
+/// `P::IS_INVERTED`. Per the DShot spec.
+const ONE_DUTY: f32 = 0.75;
+/// Duty-cycle fraction (high time / bit period) of a `0` bit, before accounting for
+/// `P::IS_INVERTED`. Per the DShot spec.
+const ZERO_DUTY: f32 = 0.375;
+/// Duty-cycle fraction of the mandatory inter-frame idle symbol, before accounting for
+/// `P::IS_INVERTED`: the line simply stays at rest.
+const IDLE_DUTY: f32 = 0.0;
+
+#[must_use]
+const fn bit_duty(bit_is_set: bool) -> f32 {
+    if bit_is_set { ONE_DUTY } else { ZERO_DUTY }
+}
+
+/// Whole ticks of `timer_clock_hz` in one bit period of `speed`, rounded to the nearest tick.
+/// [`None`] if the clock is too coarse to represent the bit period as at least one whole tick.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn bit_period_ticks(speed: DShotSpeed, timer_clock_hz: u32) -> Option<u32> {
+    let ticks = (speed.bit_time_us() * timer_clock_hz as f32 / 1_000_000.0).round();
+
+    (ticks >= 1.0).then_some(ticks as u32)
+}
+
+/// Splits `total_ticks` into a `(high_ticks, low_ticks)` pair for the given duty fraction,
+/// swapping the halves when `inverted` is set.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn split_ticks(total_ticks: u32, duty: f32, inverted: bool) -> (u32, u32) {
+    let high_ticks = ((total_ticks as f32 * duty).round() as u32).min(total_ticks);
+    let low_ticks = total_ticks - high_ticks;
+
+    if inverted { (low_ticks, high_ticks) } else { (high_ticks, low_ticks) }
 }
 
 /// Commands that occupy the lower 48 speed values.
@@ -265,6 +364,68 @@ pub enum Command {
     SignalLineERPMPeriodTelemetry,
 }
 
+/// How many identical frames a [`Command`] must be transmitted as, and the minimum time the
+/// line must then idle before the next command, per the DShot spec (see each variant's doc
+/// comment above).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandTiming {
+    /// Number of identical frames that must be sent in a row for the ESC to accept the command.
+    pub repetitions: u8,
+    /// Minimum time, in microseconds, the line must stay idle after the last repetition before
+    /// the next command may be sent.
+    pub dwell_us: u32,
+}
+
+impl Command {
+    /// This command's required transmission count and mandatory post-command dwell.
+    ///
+    /// Written as a `match` with no wildcard arm so adding a new [`Command`] variant without
+    /// giving it a timing here is a compile error, not a runtime surprise.
+    #[must_use]
+    pub const fn timing(self) -> CommandTiming {
+        const fn timing(repetitions: u8, dwell_us: u32) -> CommandTiming {
+            CommandTiming { repetitions, dwell_us }
+        }
+
+        match self {
+            Command::MotorStop => timing(1, 0),
+            Command::Beep1
+            | Command::Beep2
+            | Command::Beep3
+            | Command::Beep4
+            | Command::Beep5 => timing(1, 260_000),
+            Command::ESCInfo => timing(1, 12_000),
+            Command::SpinDirection1
+            | Command::SpinDirection2
+            | Command::ThreeDModeOn
+            | Command::ThreeDModeOff => timing(6, 0),
+            Command::SettingsRequest => timing(1, 0),
+            Command::SettingsSave => timing(6, 35_000),
+            Command::ExtendedTelemetryEnable | Command::ExtendedTelemetryDisable => timing(6, 0),
+            Command::SpinDirectionNormal | Command::SpinDirectonReversed => timing(6, 0),
+            Command::Led0On
+            | Command::Led1On
+            | Command::Led2On
+            | Command::Led3On
+            | Command::Led0Off
+            | Command::Led1Off
+            | Command::Led2Off
+            | Command::Led3Off => timing(1, 0),
+            Command::AudioStreamModeToggle | Command::SilentModeToggle => timing(1, 0),
+            Command::SignalLineTelemetryEnable
+            | Command::SignalLineTelemetryDisable
+            | Command::SignalLineContinuousERPMTelemetry
+            | Command::SignalLineContinuousERPMPeriodTelemetry => timing(6, 0),
+            Command::SignalLineTemperatureTelemetry
+            | Command::SignalLineVoltageTelemetry
+            | Command::SignalLineCurrentTelemetry
+            | Command::SignalLineConsumptionTelemetry
+            | Command::SignalLineERPMTelemetry
+            | Command::SignalLineERPMPeriodTelemetry => timing(1, 0),
+        }
+    }
+}
+
 // Gets the period shift value from raw frame data
 #[must_use]
 const fn shift_from_raw(raw: u16) -> u8 {
@@ -277,6 +438,27 @@ const fn base_from_raw(raw: u16) -> u16 {
     (raw >> 3) & 0x01FF
 }
 
+/// Motor-specific configuration needed to turn an electrical RPM reading (what all of DShot's
+/// telemetry formats actually report) into mechanical (shaft) RPM.
+///
+/// Every telemetry source used to leave this conversion to the caller, each with its own
+/// "multiply by 2 / magnet pole count" comment; [`mechanical_rpm`](StandardERpmFrame::mechanical_rpm)-style
+/// methods on the telemetry types below do it consistently instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MotorConfig {
+    /// Number of magnet pole pairs on the motor. Electrical RPM is mechanical RPM times this
+    /// many pole pairs.
+    pub pole_pairs: NonZeroU8,
+}
+
+impl MotorConfig {
+    /// Divides an electrical RPM reading down to mechanical (shaft) RPM.
+    #[must_use]
+    pub const fn mechanical_rpm(&self, electrical_rpm: u32) -> u32 {
+        electrical_rpm / self.pole_pairs.get() as u32
+    }
+}
+
 pub trait ERpmVarient: Sized {
     /// Creates a new option of a ERPM frame object given the raw frame data (after grc decoding)
     ///
@@ -346,6 +528,13 @@ impl StandardERpmFrame {
             .map_or(0, |period| 60_000_000 / period)
     }
 
+    /// Computes [`u32`] mechanical (shaft) RPM, dividing [`Self::compute_rpm`]'s electrical RPM
+    /// by `config`'s pole pair count.
+    #[must_use]
+    pub fn mechanical_rpm(&self, config: MotorConfig) -> u32 {
+        config.mechanical_rpm(self.compute_rpm())
+    }
+
     /// Returns internal 3 bit [`u8`] ``period_us`` shift value.
     #[must_use]
     pub fn shift(&self) -> u8 {
@@ -464,6 +653,44 @@ impl ExtendedERpmFrame {
         }
     }
 
+    /// Computes [`u32`] mechanical (shaft) RPM, dividing [`Self::compute_rpm`]'s electrical RPM
+    /// by `config`'s pole pair count.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PeriodComputationResult::NotRpmPacket`] when the packet type is not RPM.
+    pub fn mechanical_rpm(&self, config: MotorConfig) -> Result<u32, PeriodComputationResult> {
+        self.compute_rpm().map(|rpm| config.mechanical_rpm(rpm))
+    }
+
+    /// ESC temperature in degrees C, if this frame carries a temperature reading.
+    #[must_use]
+    pub const fn temperature_c(&self) -> Option<u8> {
+        match self.data {
+            ExtendedERpmData::Temperature(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Pack voltage in volts (raw value is 0.25V per step), if this frame carries a voltage
+    /// reading.
+    #[must_use]
+    pub fn voltage_v(&self) -> Option<f32> {
+        match self.data {
+            ExtendedERpmData::Voltage(value) => Some(f32::from(value) * 0.25),
+            _ => None,
+        }
+    }
+
+    /// Motor current in amps, if this frame carries a current reading.
+    #[must_use]
+    pub const fn current_a(&self) -> Option<u8> {
+        match self.data {
+            ExtendedERpmData::Current(value) => Some(value),
+            _ => None,
+        }
+    }
+
     /// Returns internal 3 bit [`u8`] ``period_us`` shift value.
     ///
     /// Returns [`None`] when the packet is not of type Rpm.
@@ -541,4 +768,106 @@ impl TelemetryFrame {
         }
         crc
     }
+
+    /// Returns the ESC temperature in degrees C.
+    #[must_use]
+    pub const fn temp(&self) -> u8 {
+        self.temp
+    }
+
+    /// Returns the pack voltage in centivolts.
+    #[must_use]
+    pub const fn voltage(&self) -> u16 {
+        self.voltage
+    }
+
+    /// Returns the motor current in centiamps.
+    #[must_use]
+    pub const fn current(&self) -> u16 {
+        self.current
+    }
+
+    /// Returns the consumption in mAh.
+    #[must_use]
+    pub const fn consumption(&self) -> u16 {
+        self.consumption
+    }
+
+    /// Returns the raw eRPM / 100 value. Prefer [`Self::mechanical_rpm`] for real shaft RPM.
+    #[must_use]
+    pub const fn e_rpm(&self) -> u16 {
+        self.e_rpm
+    }
+
+    /// Computes [`u32`] mechanical (shaft) RPM from [`Self::e_rpm`], dividing electrical RPM by
+    /// `config`'s pole pair count.
+    #[must_use]
+    pub fn mechanical_rpm(&self, config: MotorConfig) -> u32 {
+        config.mechanical_rpm(u32::from(self.e_rpm) * 100)
+    }
+
+    /// Returns the CRC checksum
+    #[must_use]
+    pub const fn crc(&self) -> u8 {
+        self.crc
+    }
+}
+
+/// Streaming assembler for 10-byte KISS/BLHeli [`TelemetryFrame`]s, built for feeding from a
+/// UART DMA read that may hand back anywhere from one byte to a handful of bytes at a time.
+///
+/// This only accumulates bytes and validates CRC; it has no opinion on where one frame ends and
+/// the next begins. The caller tells it that via [`Self::reset`] (e.g. on an idle-line gap, see
+/// `motor_controller::core1::read_until_idle`), which decodes whatever has been fed since the
+/// last boundary and immediately clears the assembler, so a short read or a bad CRC only
+/// desyncs the stream for the remainder of the current frame period.
+#[derive(Default)]
+pub struct TelemetryAssembler {
+    buf: [u8; 10],
+    len: usize,
+}
+
+impl TelemetryAssembler {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { buf: [0u8; 10], len: 0 }
+    }
+
+    /// Appends `chunk` (bytes from a single DMA read) to the in-progress frame. Bytes beyond
+    /// the 10-byte frame length are ignored; call [`Self::reset`] at the next frame boundary.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        for &byte in chunk {
+            if self.len < self.buf.len() {
+                self.buf[self.len] = byte;
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Number of bytes fed since the last [`Self::reset`].
+    #[must_use]
+    pub const fn buffered_len(&self) -> usize {
+        self.len
+    }
+
+    /// The bytes fed since the last [`Self::reset`], for callers that need to keep the raw frame
+    /// around (e.g. to forward it verbatim) regardless of whether it decodes.
+    #[must_use]
+    pub fn buffered_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Marks a frame boundary, decodes whatever bytes were fed since the last boundary, and
+    /// resynchronises the assembler to accept the next frame.
+    ///
+    /// Returns `None` for a short frame (fewer than 10 bytes fed) or a CRC mismatch; either way
+    /// the assembler is immediately ready for the next frame.
+    pub fn reset(&mut self) -> Option<TelemetryFrame> {
+        let frame = (self.len == self.buf.len())
+            .then(|| TelemetryFrame::from_bytes(&self.buf))
+            .flatten();
+
+        self.len = 0;
+        frame
+    }
 }
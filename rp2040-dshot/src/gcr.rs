@@ -0,0 +1,105 @@
+//! GCR decode for bidirectional DShot (BDShot) eRPM telemetry.
+//!
+//! `encoder::ERpmVarient::from_raw` expects "raw frame data (after gcr decoding)", but turning
+//! an ESC's telemetry response into that 16-bit word is the hard part: the ESC replies with a
+//! 21-bit value, transition-encoded onto the signal wire and sampled at
+//! [`DShotSpeed::gcr_bit_rate_hz`], that has to be un-transitioned and its four 5-bit symbols
+//! mapped back to nibbles first. This module is that step.
+
+use crate::encoder::DShotSpeed;
+
+/// Number of raw bits a bidirectional-DShot telemetry response carries on the wire, before
+/// undoing the transition encoding.
+const RAW_BIT_COUNT: u32 = 21;
+
+/// Reverse GCR table: maps each of the sixteen valid 5-bit symbols back to the nibble it
+/// encodes. Any other 5-bit value is not a symbol DShot's GCR alphabet ever emits.
+fn decode_symbol(symbol: u8) -> Option<u8> {
+    match symbol {
+        0x19 => Some(0x0),
+        0x1B => Some(0x1),
+        0x12 => Some(0x2),
+        0x13 => Some(0x3),
+        0x1D => Some(0x4),
+        0x15 => Some(0x5),
+        0x16 => Some(0x6),
+        0x17 => Some(0x7),
+        0x1A => Some(0x8),
+        0x09 => Some(0x9),
+        0x0A => Some(0xA),
+        0x0B => Some(0xB),
+        0x1E => Some(0xC),
+        0x0D => Some(0xD),
+        0x0E => Some(0xE),
+        0x0F => Some(0xF),
+        _ => None,
+    }
+}
+
+/// Why a GCR capture failed to decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodeError {
+    /// One of the four 5-bit quintets wasn't one of the sixteen valid GCR symbols.
+    InvalidSymbol,
+    /// The capture didn't add up to exactly the 21 bits a GCR response carries.
+    WrongBitCount,
+}
+
+/// Decodes a captured, transition-encoded bidirectional-DShot response into the 16-bit word
+/// [`crate::encoder::ERpmVarient::from_raw`] expects.
+///
+/// `received` holds the 21 raw sampled bits in its low bits. First undoes the transition
+/// encoding (`received ^ (received >> 1)`): bit 20 of the result folds in the (always absent)
+/// 22nd received bit and is discarded by masking to the low 20 bits, which are the real payload;
+/// then splits those 20 bits into four 5-bit quintets, most significant first, and maps each
+/// back to a nibble to reassemble the 16-bit word.
+pub fn decode_raw_bits(received: u32) -> Result<u16, DecodeError> {
+    let payload = (received ^ (received >> 1)) & 0xFFFFF;
+
+    let mut word: u16 = 0;
+    for quintet_idx in 0..4u32 {
+        let shift = 15 - quintet_idx * 5;
+        let symbol = ((payload >> shift) & 0x1F) as u8;
+        let nibble = decode_symbol(symbol).ok_or(DecodeError::InvalidSymbol)?;
+        word |= u16::from(nibble) << (12 - quintet_idx * 4);
+    }
+
+    Ok(word)
+}
+
+/// Quantizes a slice of alternating pulse widths (in nanoseconds, line assumed idle-high so the
+/// first width is a high pulse) into the 21-bit raw word [`decode_raw_bits`] expects, then
+/// decodes it.
+///
+/// Lets a capture method that records edge timings (RMT, a PIO program sampling a timer, ...)
+/// instead of level-sampled bits feed straight into the GCR decoder. Each pulse width is rounded
+/// to the nearest whole number of bit periods at `speed`'s [`DShotSpeed::gcr_bit_rate_hz`].
+pub fn decode_edge_durations_ns(edges: &[u32], speed: DShotSpeed) -> Result<u16, DecodeError> {
+    let bit_period_ns = 1_000_000_000u32 / speed.gcr_bit_rate_hz();
+
+    let mut received: u32 = 0;
+    let mut bits_written: u32 = 0;
+    let mut level_high = true;
+
+    for &duration_ns in edges {
+        let bit_count = ((duration_ns + bit_period_ns / 2) / bit_period_ns).max(1);
+
+        for _ in 0..bit_count {
+            if bits_written >= RAW_BIT_COUNT {
+                return Err(DecodeError::WrongBitCount);
+            }
+
+            received = (received << 1) | u32::from(level_high);
+            bits_written += 1;
+        }
+
+        level_high = !level_high;
+    }
+
+    if bits_written != RAW_BIT_COUNT {
+        return Err(DecodeError::WrongBitCount);
+    }
+
+    decode_raw_bits(received)
+}
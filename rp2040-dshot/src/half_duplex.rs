@@ -0,0 +1,93 @@
+//! Hardware-independent async driver for half-duplex bidirectional DShot (BDShot).
+//!
+//! `driver::BdDShotDriver` talks directly to an RP2040 PIO state machine. This module is the
+//! same transmit-then-listen protocol (send an [`InvertedFrame`], reverse the line, decode the
+//! ESC's GCR telemetry response) written against an abstracted [`HalfDuplexLine`] instead, so
+//! the same driver works whether the line is actually driven by a PIO program, an RMT channel,
+//! or a timer+capture peripheral.
+
+use crate::encoder::{Command, ERpmVarient, InvertedFrame, StandardERpmFrame};
+use crate::gcr;
+use embassy_time::{with_timeout, Duration, TimeoutError};
+
+/// Generous upper bound on how long an ESC's telemetry response can take to start arriving once
+/// transmission ends, mirroring the timeouts `driver::erpm_reader_task_impl` already uses for
+/// this same exchange on real hardware.
+const RESPONSE_WINDOW: Duration = Duration::from_micros(500);
+
+/// Abstracts the hardware-specific half of a bidirectional-DShot exchange: driving one signal
+/// line in DShot's transmit-then-listen pattern. Implement this for a PIO program, an RMT
+/// channel, or a timer+capture peripheral, and [`HalfDuplexDriver`] supplies the rest: frame
+/// construction, the response-window timeout, and GCR decode.
+pub trait HalfDuplexLine {
+    /// Error type surfaced by this backend's transmit/receive operations.
+    type Error;
+
+    /// Clocks `frame` out on the line. `Frame::to_duty_samples`/`Frame::to_pulse_ticks` render
+    /// it into whatever this backend's peripheral consumes.
+    #[allow(async_fn_in_trait)]
+    async fn transmit(&mut self, frame: InvertedFrame) -> Result<(), Self::Error>;
+
+    /// Reverses the line and captures the ESC's GCR telemetry response as the 21-bit raw word
+    /// [`gcr::decode_raw_bits`] expects. Called immediately after [`transmit`](Self::transmit)
+    /// returns.
+    #[allow(async_fn_in_trait)]
+    async fn receive(&mut self) -> Result<u32, Self::Error>;
+}
+
+/// Why a [`HalfDuplexDriver`] exchange failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E> {
+    /// Requested throttle was out of DShot's 0-1999 range.
+    ThrottleBoundsError { throttle: u16 },
+    /// No telemetry response arrived inside [`RESPONSE_WINDOW`].
+    Timeout,
+    /// The captured response didn't decode to a valid GCR symbol.
+    Gcr(gcr::DecodeError),
+    /// The decoded eRPM frame's CRC didn't match.
+    InvalidTelemetryChecksum,
+    /// The [`HalfDuplexLine`] backend reported an error.
+    Line(E),
+}
+
+/// Async, half-duplex bidirectional-DShot driver generic over a [`HalfDuplexLine`] backend.
+///
+/// Keeps the BDShot protocol (frame construction, response-window timeout, GCR decode) entirely
+/// hardware-independent; swapping backends moves the driver between a PIO program, an RMT
+/// channel, or a timer+capture peripheral without touching this type.
+pub struct HalfDuplexDriver<L: HalfDuplexLine> {
+    line: L,
+}
+
+impl<L: HalfDuplexLine> HalfDuplexDriver<L> {
+    pub fn new(line: L) -> Self {
+        Self { line }
+    }
+
+    /// Sends `throttle` (0-1999) and returns the ESC's decoded eRPM telemetry.
+    pub async fn write_throttle(&mut self, throttle: u16) -> Result<StandardERpmFrame, Error<L::Error>> {
+        let frame = InvertedFrame::from_throttle(throttle, true)
+            .ok_or(Error::ThrottleBoundsError { throttle })?;
+
+        self.exchange(frame).await
+    }
+
+    /// Sends `command` and returns the ESC's decoded eRPM telemetry.
+    pub async fn write_command(&mut self, command: Command) -> Result<StandardERpmFrame, Error<L::Error>> {
+        self.exchange(InvertedFrame::from_command(command, true)).await
+    }
+
+    async fn exchange(&mut self, frame: InvertedFrame) -> Result<StandardERpmFrame, Error<L::Error>> {
+        self.line.transmit(frame).await.map_err(Error::Line)?;
+
+        let raw = with_timeout(RESPONSE_WINDOW, self.line.receive())
+            .await
+            .map_err(|_: TimeoutError| Error::Timeout)?
+            .map_err(Error::Line)?;
+
+        let word = gcr::decode_raw_bits(raw).map_err(Error::Gcr)?;
+
+        StandardERpmFrame::from_raw(word).ok_or(Error::InvalidTelemetryChecksum)
+    }
+}
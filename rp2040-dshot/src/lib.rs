@@ -12,8 +12,13 @@ pub use program::StandardDShotTimings as StandardDShotTimings;
 pub use program::BdDShotTimings as BdDShotTimings;
 #[cfg(feature = "driver")]
 pub mod driver;
+#[cfg(feature = "driver")]
+pub mod half_duplex;
 
-mod encoder;
+pub mod encoder;
+pub mod gcr;
+pub mod control;
+pub mod sequencer;
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "thiserror", derive(thiserror_no_std::Error))]
@@ -51,6 +56,14 @@ pub enum Error {
     /// State machine split faliure, empty pointer!
     #[cfg_attr(feature = "thiserror", error("SM Split Faliure, empty pointer!"))]
     SmSplitFaliure,
+    /// Writing a firmware update chunk to the DFU partition failed.
+    #[cfg(feature = "driver")]
+    #[cfg_attr(feature = "thiserror", error("Failed to write firmware update chunk to flash"))]
+    FirmwareWriteError,
+    /// The updated firmware image failed verification and was not marked bootable.
+    #[cfg(feature = "driver")]
+    #[cfg_attr(feature = "thiserror", error("Firmware update verification failed"))]
+    UpdateVerifyFailed,
 }
 
 #[cfg(feature = "driver")]
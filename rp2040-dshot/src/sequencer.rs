@@ -0,0 +1,75 @@
+//! Command sequencing: enforces the repetition count and mandatory dwell time each [`Command`]
+//! requires before the line is free again.
+//!
+//! Several `Command` variants are annotated "Needs 6 transmissions" and/or "Wait at least Nms
+//! before next command" ([`Command::timing`]), but nothing checked those invariants; every
+//! caller had to re-discover and re-implement them. [`CommandSequencer`] is a pull-based
+//! scheduler: feed it a command, then call [`CommandSequencer::next_frame`] with the current
+//! time until it stops returning frames, and use [`CommandSequencer::dwell_deadline_us`] to know
+//! how long the line must stay idle afterward. It has no opinion on how time is measured or
+//! waited on, so the same sequencer works behind a blocking delay loop or an
+//! `embassy_time::Timer`.
+
+use crate::encoder::{Command, DShotVariant, Frame};
+
+/// Emits a [`Command`]'s required repetitions, then reports the mandatory post-command dwell
+/// deadline.
+///
+/// `now_us` passed to [`Self::next_frame`] and read back from [`Self::dwell_deadline_us`] is an
+/// arbitrary monotonic microsecond count from whatever clock the caller is using, e.g.
+/// `embassy_time::Instant::now().as_micros()` or a free-running hardware timer's tick count
+/// converted to microseconds.
+pub struct CommandSequencer<P: DShotVariant> {
+    frame: Frame<P>,
+    dwell_us: u32,
+    remaining_repetitions: u8,
+    dwell_deadline_us: Option<u64>,
+}
+
+impl<P: DShotVariant> CommandSequencer<P> {
+    /// Starts sequencing `command`, looking up its repetition count and dwell time from
+    /// [`Command::timing`].
+    #[must_use]
+    pub const fn start(command: Command, request_telemetry: bool) -> Self {
+        let timing = command.timing();
+        Self {
+            frame: Frame::from_command(command, request_telemetry),
+            dwell_us: timing.dwell_us,
+            remaining_repetitions: timing.repetitions,
+            dwell_deadline_us: None,
+        }
+    }
+
+    /// Pulls the next frame to transmit.
+    ///
+    /// Returns the command's frame while repetitions remain. Once the last repetition has been
+    /// returned, records the dwell deadline (readable via [`Self::dwell_deadline_us`]) and
+    /// returns `None` from then on.
+    pub fn next_frame(&mut self, now_us: u64) -> Option<Frame<P>> {
+        if self.remaining_repetitions == 0 {
+            return None;
+        }
+
+        self.remaining_repetitions -= 1;
+
+        if self.remaining_repetitions == 0 {
+            self.dwell_deadline_us = Some(now_us.saturating_add(u64::from(self.dwell_us)));
+        }
+
+        Some(self.frame)
+    }
+
+    /// The time, on the same clock passed to [`Self::next_frame`], the line must idle until
+    /// before the next command may be sent. `None` until every repetition has been emitted.
+    #[must_use]
+    pub const fn dwell_deadline_us(&self) -> Option<u64> {
+        self.dwell_deadline_us
+    }
+
+    /// Whether this sequence has finished: every repetition emitted and, as of `now_us`, the
+    /// mandatory dwell (if any) has elapsed.
+    #[must_use]
+    pub fn is_complete(&self, now_us: u64) -> bool {
+        self.dwell_deadline_us.is_some_and(|deadline| now_us >= deadline)
+    }
+}
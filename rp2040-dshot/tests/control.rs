@@ -0,0 +1,60 @@
+use rp2040_dshot::control::{Gains, RpmController};
+
+const GAINS: Gains = Gains {
+    kp: 1.0,
+    ki: 0.0,
+    kd: 0.0,
+    integral_clamp: 1000.0,
+    max_slew_rate: 50.0,
+};
+
+#[test]
+fn update_slew_rate_limits_a_large_setpoint_jump() {
+    let mut controller = RpmController::new(GAINS);
+
+    // A 10000 RPM error times kp=1.0 asks for far more throttle than one call may apply.
+    let throttle = controller.update(10_000.0, 0.0, 0.01);
+
+    assert_eq!(throttle, 50);
+    assert_eq!(controller.last_throttle(), 50);
+}
+
+#[test]
+fn update_never_exceeds_the_dshot_throttle_range() {
+    let mut controller = RpmController::new(Gains { max_slew_rate: 5000.0, ..GAINS });
+
+    let throttle = controller.update(1_000_000.0, 0.0, 1.0);
+    assert!(throttle <= 1999);
+
+    let throttle = controller.update(-1_000_000.0, 0.0, 1.0);
+    assert_eq!(throttle, 0);
+}
+
+#[test]
+fn integral_term_is_clamped_to_the_configured_bound() {
+    let gains = Gains { kp: 0.0, ki: 1.0, kd: 0.0, integral_clamp: 10.0, max_slew_rate: 1999.0 };
+    let mut controller = RpmController::new(gains);
+
+    // A large, sustained error would otherwise wind the integral up far past the clamp.
+    for _ in 0..100 {
+        controller.update(100_000.0, 0.0, 1.0);
+    }
+
+    // With kp=0 the output is exactly ki * integral, clamped to the integral_clamp bound.
+    assert_eq!(controller.last_throttle(), 10);
+}
+
+#[test]
+fn hold_last_keeps_the_last_commanded_throttle_and_drops_the_derivative_reference() {
+    let mut controller = RpmController::new(Gains { kd: 1.0, ..GAINS });
+
+    let first = controller.update(1000.0, 500.0, 0.1);
+    let held = controller.hold_last();
+    assert_eq!(held, first);
+    assert_eq!(controller.last_throttle(), first);
+
+    // `hold_last` cleared the previous measurement, so the next update has nothing to
+    // differentiate against and uses a zero derivative term instead of a stale rate.
+    let next = controller.update(1000.0, 500.0, 0.1);
+    assert!(next <= 1999);
+}
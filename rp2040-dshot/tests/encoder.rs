@@ -1,5 +1,5 @@
 
-use rp2040_dshot::encoder::DShotSpeed;
+use rp2040_dshot::encoder::{DShotSpeed, TelemetryAssembler, TelemetryFrame};
 
 #[test]
 fn test_gcr_rate_ratio() {
@@ -21,4 +21,52 @@ fn test_gcr_rate_ratio() {
             ratio
         );
     }
+}
+
+fn valid_frame_bytes() -> [u8; 10] {
+    let mut data = [25u8, 0, 126, 0, 10, 0, 5, 1, 200, 0];
+    data[9] = TelemetryFrame::compute_crc(&data[..9]);
+    data
+}
+
+#[test]
+fn test_telemetry_assembler_assembles_across_partial_reads() {
+    let data = valid_frame_bytes();
+    let mut assembler = TelemetryAssembler::new();
+
+    // Feed the frame split across several differently-sized chunks, as a UART DMA read might.
+    assembler.feed(&data[..3]);
+    assembler.feed(&data[3..4]);
+    assembler.feed(&data[4..]);
+
+    assert_eq!(assembler.buffered_len(), data.len());
+    let frame = assembler.reset().expect("valid frame should decode");
+    assert_eq!(frame.temp(), 25);
+    assert_eq!(assembler.buffered_len(), 0);
+}
+
+#[test]
+fn test_telemetry_assembler_rejects_bad_crc_and_resyncs() {
+    let mut data = valid_frame_bytes();
+    data[9] ^= 0xFF; // corrupt the CRC byte
+    let mut assembler = TelemetryAssembler::new();
+
+    assembler.feed(&data);
+    assert!(assembler.reset().is_none());
+    assert_eq!(assembler.buffered_len(), 0);
+
+    // The assembler must be immediately usable for the next frame after a bad CRC.
+    let good = valid_frame_bytes();
+    assembler.feed(&good);
+    assert!(assembler.reset().is_some());
+}
+
+#[test]
+fn test_telemetry_assembler_rejects_short_frame() {
+    let data = valid_frame_bytes();
+    let mut assembler = TelemetryAssembler::new();
+
+    assembler.feed(&data[..7]);
+    assert_eq!(assembler.buffered_len(), 7);
+    assert!(assembler.reset().is_none());
 }
\ No newline at end of file
@@ -0,0 +1,87 @@
+use rp2040_dshot::gcr::{decode_raw_bits, DecodeError};
+
+/// Forward GCR table, the exact inverse of `gcr::decode_symbol` (private to the crate), used
+/// here only to build known-good raw captures for round-trip tests.
+fn encode_nibble(nibble: u8) -> u8 {
+    match nibble {
+        0x0 => 0x19,
+        0x1 => 0x1B,
+        0x2 => 0x12,
+        0x3 => 0x13,
+        0x4 => 0x1D,
+        0x5 => 0x15,
+        0x6 => 0x16,
+        0x7 => 0x17,
+        0x8 => 0x1A,
+        0x9 => 0x09,
+        0xA => 0x0A,
+        0xB => 0x0B,
+        0xC => 0x1E,
+        0xD => 0x0D,
+        0xE => 0x0E,
+        0xF => 0x0F,
+        _ => unreachable!("nibble out of range"),
+    }
+}
+
+/// Independent reference GCR/BDShot transition encoder, built from the wire protocol itself
+/// (not from `gcr`'s decoder): given a 20-bit payload (four 5-bit quintets, MSB first, in bits
+/// 0-19), produces the 21-bit sequence a real ESC would transmit.
+///
+/// Transition encoding sends each payload bit as `payload[i] = received[i] ^ received[i + 1]`,
+/// with the topmost received bit fixed at the idle-high reference level every real encoder
+/// starts from. Building `received` means solving that recurrence top-down:
+/// `received[i] = payload[i] ^ received[i + 1]`.
+fn payload_to_raw_bits(payload: u32) -> u32 {
+    let mut received: u32 = 1 << 20; // fixed reference bit the encoder starts from
+    for i in (0..20).rev() {
+        let payload_bit = (payload >> i) & 1;
+        let upper_bit = (received >> (i + 1)) & 1;
+        received |= (payload_bit ^ upper_bit) << i;
+    }
+
+    received
+}
+
+fn encode_word_to_raw_bits(word: u16) -> u32 {
+    let mut payload: u32 = 0;
+    for quintet_idx in 0..4u32 {
+        let nibble = ((word >> (12 - quintet_idx * 4)) & 0xF) as u8;
+        let symbol = u32::from(encode_nibble(nibble));
+        payload |= symbol << (15 - quintet_idx * 5);
+    }
+
+    payload_to_raw_bits(payload)
+}
+
+#[test]
+fn round_trips_every_nibble_in_every_quintet_position() {
+    for word in [0x0000u16, 0x1234, 0xFFFF, 0xABCD, 0x8421, 0x0F0F] {
+        let raw = encode_word_to_raw_bits(word);
+        assert_eq!(decode_raw_bits(raw), Ok(word), "word {:#06x} failed to round-trip", word);
+    }
+}
+
+#[test]
+fn rejects_an_invalid_symbol_in_the_first_quintet() {
+    // 0x00 is never emitted by the forward table (it and 0x1F both violate GCR's run-length
+    // constraint), so forcing the top quintet to it is guaranteed to hit `InvalidSymbol`.
+    let mut payload: u32 = 0;
+    payload |= u32::from(encode_nibble(0x1)) << 10;
+    payload |= u32::from(encode_nibble(0x2)) << 5;
+    payload |= u32::from(encode_nibble(0x3));
+
+    let raw = payload_to_raw_bits(payload);
+    assert_eq!(decode_raw_bits(raw), Err(DecodeError::InvalidSymbol));
+}
+
+#[test]
+fn rejects_an_invalid_symbol_in_the_last_quintet() {
+    let mut payload: u32 = u32::from(encode_nibble(0x1)) << 15;
+    payload |= u32::from(encode_nibble(0x2)) << 10;
+    payload |= u32::from(encode_nibble(0x3)) << 5;
+    payload |= 0x1F; // never emitted by the forward table
+
+    let raw = payload_to_raw_bits(payload);
+    assert_eq!(decode_raw_bits(raw), Err(DecodeError::InvalidSymbol));
+}
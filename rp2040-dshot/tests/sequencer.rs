@@ -0,0 +1,52 @@
+use rp2040_dshot::encoder::{Command, StandardDShotVariant};
+use rp2040_dshot::sequencer::CommandSequencer;
+
+type Sequencer = CommandSequencer<StandardDShotVariant>;
+
+#[test]
+fn emits_every_repetition_then_records_the_dwell_deadline() {
+    // SpinDirectionNormal needs 6 repetitions and no dwell (see `Command::timing`).
+    let mut sequencer = Sequencer::start(Command::SpinDirectionNormal, false);
+
+    for _ in 0..6 {
+        assert!(sequencer.next_frame(0).is_some());
+    }
+
+    assert!(sequencer.next_frame(0).is_none());
+    assert_eq!(sequencer.dwell_deadline_us(), Some(0));
+}
+
+#[test]
+fn dwell_deadline_is_relative_to_the_time_of_the_final_repetition() {
+    // ESCInfo is a single-repetition command with a 12_000us mandatory dwell.
+    let mut sequencer = Sequencer::start(Command::ESCInfo, false);
+
+    assert!(sequencer.next_frame(1_000).is_some());
+    assert_eq!(sequencer.dwell_deadline_us(), Some(13_000));
+    assert!(sequencer.next_frame(1_000).is_none());
+}
+
+#[test]
+fn is_complete_only_once_every_repetition_is_sent_and_the_dwell_has_elapsed() {
+    let mut sequencer = Sequencer::start(Command::ESCInfo, false);
+
+    // Not complete before even the one repetition has gone out.
+    assert!(!sequencer.is_complete(0));
+
+    sequencer.next_frame(0);
+    assert_eq!(sequencer.dwell_deadline_us(), Some(12_000));
+
+    // Complete is about elapsed dwell time, not about repetitions alone.
+    assert!(!sequencer.is_complete(11_999));
+    assert!(sequencer.is_complete(12_000));
+    assert!(sequencer.is_complete(12_001));
+}
+
+#[test]
+fn motor_stop_has_no_dwell_and_completes_immediately() {
+    let mut sequencer = Sequencer::start(Command::MotorStop, false);
+
+    assert!(sequencer.next_frame(500).is_some());
+    assert_eq!(sequencer.dwell_deadline_us(), Some(500));
+    assert!(sequencer.is_complete(500));
+}